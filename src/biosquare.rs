@@ -1,5 +1,13 @@
-use matreex::{Matrix, WrappingIndex};
+use crate::engine::Engine;
+use crate::genesis::Density;
+use crate::pattern::Pattern;
+use crate::rule::Rule;
+use crate::topology::Topology;
+use anyhow::Result;
+use matreex::{Index, Matrix, Shape};
 use rand::RngExt;
+use std::ops::AddAssign;
+use std::path::Path;
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Cell {
@@ -19,6 +27,14 @@ impl Cell {
     pub fn revive(&mut self) {
         *self = Self::Alive;
     }
+
+    /// Returns the opposite state.
+    pub fn flipped(self) -> Self {
+        match self {
+            Self::Dead => Self::Alive,
+            Self::Alive => Self::Dead,
+        }
+    }
 }
 
 impl From<bool> for Cell {
@@ -27,116 +43,447 @@ impl From<bool> for Cell {
     }
 }
 
+/// Numbers of cells that changed state over a generation.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Changes {
+    pub births: usize,
+    pub deaths: usize,
+}
+
+impl Changes {
+    /// Returns the number of cells that changed state either way.
+    pub fn activity(self) -> usize {
+        self.births + self.deaths
+    }
+}
+
+impl AddAssign for Changes {
+    fn add_assign(&mut self, other: Self) {
+        self.births += other.births;
+        self.deaths += other.deaths;
+    }
+}
+
+/// Rectangular region of the world, in coordinates relative to the top-left
+/// corner of the genesis.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Rect {
+    pub top: isize,
+    pub left: isize,
+    pub nrows: usize,
+    pub ncols: usize,
+}
+
+impl Rect {
+    pub fn new(top: isize, left: isize, shape: Shape) -> Self {
+        Self {
+            top,
+            left,
+            nrows: shape.nrows,
+            ncols: shape.ncols,
+        }
+    }
+
+    /// Returns the smallest rectangle containing all `locations`, or [`None`]
+    /// if there are none.
+    pub fn enclosing<I>(locations: I) -> Option<Self>
+    where
+        I: IntoIterator<Item = (isize, isize)>,
+    {
+        let mut locations = locations.into_iter();
+        let (row, col) = locations.next()?;
+        let (top, left, bottom, right) = locations.fold(
+            (row, col, row, col),
+            |(top, left, bottom, right), (row, col)| {
+                (top.min(row), left.min(col), bottom.max(row), right.max(col))
+            },
+        );
+        Some(Self {
+            top,
+            left,
+            nrows: bottom.abs_diff(top) + 1,
+            ncols: right.abs_diff(left) + 1,
+        })
+    }
+
+    pub fn shape(self) -> Shape {
+        Shape::new(self.nrows, self.ncols)
+    }
+
+    /// Returns the numbers of rows and columns as signed numbers, to do
+    /// arithmetic with locations.
+    pub fn extents(self) -> (isize, isize) {
+        // Rectangles cover cell matrices, whose numbers of rows and columns
+        // are guaranteed to be within `0..=isize::MAX as usize` due to
+        // allocation limits, or regions of similar sizes such as bounding
+        // boxes and the viewport. Therefore, the following casts are always
+        // safe.
+        (self.nrows as isize, self.ncols as isize)
+    }
+
+    /// Returns the location of the cell at `index` within a matrix covering
+    /// the rectangle.
+    pub fn location(self, index: Index) -> (isize, isize) {
+        // The index lies within the extents, so the following casts are
+        // always safe.
+        (
+            self.top + index.row as isize,
+            self.left + index.col as isize,
+        )
+    }
+
+    /// Returns the location of the center, rounded towards the top-left.
+    pub fn center(self) -> (isize, isize) {
+        // Half of any `usize` fits in an `isize`, so the following casts are
+        // always safe.
+        let row = self.top + (self.nrows / 2) as isize;
+        let col = self.left + (self.ncols / 2) as isize;
+        (row, col)
+    }
+
+    /// Returns the rectangle of the same shape centered at the given
+    /// location.
+    pub fn centered_at(self, (row, col): (isize, isize)) -> Self {
+        let top = row - (self.nrows / 2) as isize;
+        let left = col - (self.ncols / 2) as isize;
+        Self { top, left, ..self }
+    }
+}
+
+/// Generation of a state, along with the numbers of cells born and died up to
+/// it, which the cells alone do not tell.
+#[derive(Debug, Clone, Copy)]
+pub struct Stamp {
+    pub generation: usize,
+    pub changes: Option<Changes>,
+    pub total_changes: Option<Changes>,
+}
+
+/// Locations of the cells flipped since last taken, in no particular order.
+///
+/// A cell flipped twice is back to its state, so only the cells listed an
+/// odd number of times have changed.
+#[derive(Debug, Default)]
+struct Journal {
+    flips: Vec<(isize, isize)>,
+    /// Whether `flips` may list a cell more than once.
+    overlapping: bool,
+    /// Length of `flips` when the cells listed more than once were last
+    /// cancelled out.
+    compacted: usize,
+}
+
+impl Journal {
+    /// Length of `flips` below which cancelling out is left to [`Self::take`].
+    const COMPACT_MIN: usize = 1 << 12;
+
+    fn extend(&mut self, flips: &[(isize, isize)]) {
+        self.overlapping |= !self.flips.is_empty() && !flips.is_empty();
+        self.flips.extend_from_slice(flips);
+        // Cancelling out as the journal doubles keeps a long run of
+        // generations between takes from piling up.
+        if self.overlapping && self.flips.len() > 2 * self.compacted.max(Self::COMPACT_MIN) {
+            self.compact();
+        }
+    }
+
+    /// Drops the cells listed an even number of times, and the duplicates of
+    /// the others.
+    fn compact(&mut self) {
+        self.flips.sort_unstable();
+        self.flips = self
+            .flips
+            .chunk_by(|a, b| a == b)
+            .filter(|run| run.len() % 2 == 1)
+            .map(|run| run[0])
+            .collect();
+        self.overlapping = false;
+        self.compacted = self.flips.len();
+    }
+
+    /// Returns the cells flipped, each once, and empties the journal.
+    fn take(&mut self) -> Vec<(isize, isize)> {
+        if self.overlapping {
+            self.compact();
+        }
+        self.compacted = 0;
+        std::mem::take(&mut self.flips)
+    }
+}
+
 #[derive(Debug)]
 pub struct BioSquare {
     generation: usize,
-    population: usize,
-    current: Matrix<Cell>,
-    next: Matrix<Cell>,
+    engine: Box<dyn Engine>,
+    /// Cells flipped since last taken, or [`None`] if not kept track of.
+    journal: Option<Journal>,
+}
+
+impl Clone for BioSquare {
+    /// Copies the world, without keeping track of the cells flipped in the
+    /// copy.
+    fn clone(&self) -> Self {
+        let generation = self.generation;
+        let engine = self.engine.clone();
+        let journal = None;
+        Self {
+            generation,
+            engine,
+            journal,
+        }
+    }
 }
 
 impl BioSquare {
-    pub fn new(genesis: Matrix<Cell>) -> Self {
+    pub fn new(engine: Box<dyn Engine>) -> Self {
         let generation = 0;
-        let population = genesis
-            .iter_elements()
-            .filter(|cell| cell.is_alive())
-            .count();
-        let current = genesis;
-        let next = current.clone();
-
+        let journal = None;
         Self {
             generation,
-            population,
-            current,
-            next,
+            engine,
+            journal,
         }
     }
 
-    pub fn observe(&self) -> &Matrix<Cell> {
-        &self.current
+    /// Returns a copy of the whole world, or of its bounding box if it is
+    /// infinite, see [`Engine::snapshot`].
+    pub fn observe(&self) -> Result<Matrix<Cell>> {
+        self.engine.snapshot()
+    }
+
+    /// Returns the cell at the given location, see [`Engine::get`].
+    pub fn get(&self, row: isize, col: isize) -> Cell {
+        self.engine.get(row, col)
+    }
+
+    /// Returns the smallest region containing all alive cells, or [`None`]
+    /// if there are none.
+    pub fn bounding_box(&self) -> Option<Rect> {
+        self.engine.bounding_box()
+    }
+
+    /// Returns the mean location of the alive cells, rounded to the nearest
+    /// cell, or [`None`] if there are none.
+    pub fn centroid(&self) -> Option<(isize, isize)> {
+        // Locations are taken relative to the bounding box, which keeps the
+        // sums precise however far the cells are from the genesis.
+        let Rect { top, left, .. } = self.bounding_box()?;
+        let (count, rows, cols) = self.engine.alive().into_iter().fold(
+            (0.0, 0.0, 0.0),
+            |(count, rows, cols), (row, col)| {
+                (
+                    count + 1.0,
+                    rows + row.abs_diff(top) as f64,
+                    cols + col.abs_diff(left) as f64,
+                )
+            },
+        );
+        // The mean lies within the bounding box, so the following casts are
+        // always safe.
+        let row = top + (rows / count).round() as isize;
+        let col = left + (cols / count).round() as isize;
+        Some((row, col))
+    }
+
+    /// Returns a hash of the alive cells, see [`Engine::digest`].
+    pub fn digest(&self) -> u64 {
+        self.engine.digest()
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.engine.shape()
     }
 
     pub fn generation(&self) -> usize {
         self.generation
     }
 
+    /// Returns the generation and the numbers of cells born and died, to be
+    /// restored with [`Self::restore`].
+    pub fn stamp(&self) -> Stamp {
+        Stamp {
+            generation: self.generation,
+            changes: self.changes(),
+            total_changes: self.total_changes(),
+        }
+    }
+
     pub fn population(&self) -> usize {
-        self.population
+        self.engine.population()
+    }
+
+    pub fn rule(&self) -> Rule {
+        self.engine.rule()
+    }
+
+    /// Returns the numbers of cells born and died in the last generation, see
+    /// [`Engine::changes`].
+    pub fn changes(&self) -> Option<Changes> {
+        self.engine.changes()
     }
 
+    /// Returns the numbers of cells born and died since the genesis, see
+    /// [`Engine::total_changes`].
+    pub fn total_changes(&self) -> Option<Changes> {
+        self.engine.total_changes()
+    }
+
+    /// Returns the fraction of alive cells in the world, or within its
+    /// bounding box if it is infinite.
     pub fn density(&self) -> f64 {
-        self.population as f64 / self.observe().size() as f64
+        let shape = match self.topology() {
+            Topology::Infinite => self.bounding_box().unwrap_or_default().shape(),
+            _ => self.shape(),
+        };
+        let area = shape.nrows as f64 * shape.ncols as f64;
+        if area == 0.0 {
+            return 0.0;
+        }
+        self.population() as f64 / area
+    }
+
+    pub fn topology(&self) -> Topology {
+        self.engine.topology()
+    }
+
+    /// Writes the current world to a pattern file, see [`Pattern::write`].
+    pub fn save<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        // The pattern is made of the alive cells rather than a copy of the
+        // world, as those of an infinite one may be arbitrarily far apart.
+        let rect = match self.topology() {
+            Topology::Infinite => self.bounding_box().unwrap_or_default(),
+            _ => Rect::new(0, 0, self.shape()),
+        };
+        let alive = self
+            .engine
+            .alive()
+            .into_iter()
+            .map(|(row, col)| (row.abs_diff(rect.top), col.abs_diff(rect.left)))
+            .collect();
+        let pattern = Pattern::new(rect.shape(), alive, Some(self.rule()));
+        let comments = [
+            format!("Generation {}", self.generation),
+            format!("Topology {}", self.topology()),
+        ];
+        pattern.write(path, &comments)
     }
 
+    /// Starts keeping track of the cells flipped, to be taken with
+    /// [`Self::take_flips`], or returns `false` if the engine cannot tell
+    /// which cells it flips.
+    pub fn keep_flips(&mut self) -> bool {
+        if self.engine.flips().is_none() {
+            return false;
+        }
+        self.journal = Some(Journal::default());
+        true
+    }
+
+    /// Returns the locations of the cells flipped since last taken, each
+    /// once, or [`None`] if not kept track of.
+    pub fn take_flips(&mut self) -> Option<Vec<(isize, isize)>> {
+        self.journal.as_mut().map(Journal::take)
+    }
+
+    /// Replaces the cell at the given location, see [`Engine::set`].
+    pub fn set(&mut self, row: isize, col: isize, cell: Cell) {
+        let before = self.engine.get(row, col);
+        self.engine.set(row, col, cell);
+        if let Some(journal) = &mut self.journal
+            && self.engine.get(row, col) != before
+        {
+            journal.extend(&[(row, col)]);
+        }
+    }
+
+    /// Flips the cell at the given location, and returns its new state.
+    pub fn toggle(&mut self, row: isize, col: isize) -> Cell {
+        let cell = self.get(row, col).flipped();
+        self.set(row, col, cell);
+        cell
+    }
+
+    /// Flips the cells at the given locations and sets the generation along
+    /// with the numbers of cells born and died, without evolving, to move to
+    /// another state in history.
+    ///
+    /// The cells flipped are not kept track of, as the history already
+    /// knows them.
+    pub fn restore<I>(&mut self, flips: I, stamp: Stamp)
+    where
+        I: IntoIterator<Item = (isize, isize)>,
+    {
+        for (row, col) in flips {
+            let cell = self.engine.get(row, col).flipped();
+            self.engine.set(row, col, cell);
+        }
+        self.generation = stamp.generation;
+        if let (Some(changes), Some(total_changes)) = (stamp.changes, stamp.total_changes) {
+            self.engine.set_changes(changes, total_changes);
+        }
+    }
+
+    /// Replaces the cells within the region covered by the genesis with
+    /// random ones, alive with probability `density`.
+    pub fn reseed<R>(&mut self, rng: &mut R, density: Density)
+    where
+        R: RngExt,
+    {
+        self.for_each_location(|biosquare, row, col| {
+            let cell = Cell::from(rng.random_bool(density.value()));
+            biosquare.set(row, col, cell);
+        });
+    }
+
+    /// Flips random cells within the region covered by the genesis.
     pub fn random_flip<R>(&mut self, rng: &mut R)
     where
         R: RngExt,
     {
         const FLIP_RATE: f64 = 0.01;
 
-        for cell in self.current.iter_elements_mut() {
+        self.for_each_location(|biosquare, row, col| {
             if rng.random_bool(FLIP_RATE) {
-                match cell {
-                    Cell::Dead => {
-                        cell.revive();
-                        self.population += 1;
-                    }
-                    Cell::Alive => {
-                        cell.die();
-                        self.population -= 1;
-                    }
-                }
+                biosquare.toggle(row, col);
             }
-        }
+        });
     }
 
-    pub fn evolve(&mut self) {
-        const OFFSETS: [(isize, isize); 8] = [
-            (-1, -1),
-            (-1, 0),
-            (-1, 1),
-            (0, -1),
-            (0, 1),
-            (1, -1),
-            (1, 0),
-            (1, 1),
-        ];
+    /// Calls `f` with each location within the region covered by the
+    /// genesis, row by row.
+    fn for_each_location<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&mut Self, isize, isize),
+    {
+        let (nrows, ncols) = Rect::new(0, 0, self.shape()).extents();
+        for row in 0..nrows {
+            for col in 0..ncols {
+                f(self, row, col);
+            }
+        }
+    }
 
-        self.generation += 1;
-
-        for (index, cell) in self.next.iter_elements_mut_with_index() {
-            let neighbors = OFFSETS
-                .into_iter()
-                .filter(|offset| {
-                    // For a cell matrix, the numbers of rows and columns are
-                    // guaranteed to be within `0..=isize::MAX as usize` due
-                    // to allocation limits. Therefore, the following cast is
-                    // always safe.
-                    let index = WrappingIndex::new(
-                        index.row as isize + offset.0,
-                        index.col as isize + offset.1,
-                    );
-                    self.current[index].is_alive()
-                })
-                .count();
-
-            match cell {
-                Cell::Dead => {
-                    if neighbors == 3 {
-                        cell.revive();
-                        self.population += 1;
-                    }
-                }
-                Cell::Alive => {
-                    if !(2..=3).contains(&neighbors) {
-                        cell.die();
-                        self.population -= 1;
-                    }
+    /// Evolves the world by the given number of generations.
+    pub fn advance(&mut self, generations: usize) {
+        self.generation += generations;
+        match &mut self.journal {
+            // Generations are evolved one at a time to take note of the cells
+            // flipped by each.
+            Some(journal) => {
+                for _ in 0..generations {
+                    self.engine.evolve();
+                    journal.extend(self.engine.flips().unwrap_or_default());
                 }
             }
+            None => self.engine.advance(generations),
         }
+    }
 
-        self.current.overwrite(&self.next);
+    /// Evolves the world up to the given generation, if not already past it.
+    pub fn jump(&mut self, generation: usize) {
+        self.advance(generation.saturating_sub(self.generation));
     }
 }
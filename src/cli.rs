@@ -1,12 +1,27 @@
-use crate::filter::{Bit, Block, Dye, Emoji, Filter, Hanzi};
-use crate::genesis::Density;
+use crate::config::{Config, Entry};
+use crate::engine::EngineKind;
+use crate::filter::{Bit, Block, Braille, Dye, Emoji, Filter, Half, Hanzi};
+use crate::genesis::{Density, Offset};
+use crate::keymap::{Binding, Keymap};
+use crate::rule::Rule;
 use crate::screen::FpsMax;
-use clap::{Arg, ArgAction, ArgMatches, ValueEnum, command, value_parser};
+use crate::stability::OnStable;
+use crate::topology::Topology;
+use anyhow::{Result, bail};
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Arg, ArgAction, ArgMatches, Command, ValueEnum, command, value_parser};
 use crossterm::style::Color;
+use eoe::QuitOnError;
+use std::env;
+use std::error::Error;
+use std::num::NonZeroUsize;
+use std::path::PathBuf;
 use std::sync::LazyLock;
+use std::thread;
 
 static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
-    command!()
+    let mut command = command!()
         .args([
             Arg::new("nrows")
                 .short('r')
@@ -22,6 +37,11 @@ static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
                 .value_parser(value_parser!(usize))
                 .default_value("40")
                 .help("Number of columns"),
+            Arg::new("fit")
+                .long("fit")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["nrows", "ncols", "headless"])
+                .help("Fit the world to the terminal"),
             Arg::new("seed")
                 .long("seed")
                 .value_name("STRING")
@@ -33,6 +53,67 @@ static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
                 .value_parser(value_parser!(f64))
                 .default_value("0.5")
                 .help("Initial population density (use default if out of range [0.0, 1.0])"),
+            Arg::new("pattern")
+                .long("pattern")
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Pattern file (RLE, plaintext or Life 1.06) to place in an otherwise dead world"),
+            Arg::new("offset")
+                .long("offset")
+                .value_name("ROW,COL")
+                .value_parser(value_parser!(Offset))
+                .help("Top-left corner of the pattern (centered if omitted)"),
+            Arg::new("rule")
+                .long("rule")
+                .value_name("RULESTRING")
+                .value_parser(value_parser!(Rule))
+                .help(
+                    "Birth/survival rule in `B3/S23` or `23/3` notation \
+                     [default: rule of the pattern, or B3/S23]",
+                ),
+            Arg::new("topology")
+                .long("topology")
+                .value_name("TOPOLOGY")
+                .value_parser(value_parser!(Topology))
+                .hide_possible_values(true)
+                .help(Topology::help(
+                    "Boundary topology of the world [default: infinite for hashlife and sparse, otherwise torus]",
+                    None,
+                )),
+            Arg::new("engine")
+                .long("engine")
+                .value_name("ENGINE")
+                .value_parser(value_parser!(EngineKind))
+                .default_value("naive")
+                .hide_default_value(true)
+                .hide_possible_values(true)
+                .help(EngineKind::help(
+                    "Algorithm to evolve the world",
+                    Some(EngineKind::Naive),
+                )),
+            Arg::new("threads")
+                .long("threads")
+                .value_name("INTEGER")
+                .value_parser(value_parser!(NonZeroUsize))
+                .help("Number of threads to evolve the world [default: available parallelism]"),
+            Arg::new("memory-max")
+                .long("memory-max")
+                .value_name("MEBIBYTES")
+                .value_parser(value_parser!(usize))
+                .default_value("1024")
+                .help("Memory of the hashlife node cache before garbage collection between steps"),
+            Arg::new("step")
+                .long("step")
+                .value_name("EXPONENT")
+                .value_parser(value_parser!(u32).range(0..usize::BITS as i64))
+                .default_value("0")
+                .help("Evolve 2^EXPONENT generations per frame"),
+            Arg::new("jump")
+                .long("jump")
+                .value_name("GENERATION")
+                .value_parser(value_parser!(usize))
+                .default_value("0")
+                .help("Generation to start from"),
             Arg::new("filter")
                 .long("filter")
                 .value_name("FILTER")
@@ -53,7 +134,7 @@ static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
                 .hide_default_value(true)
                 .hide_possible_values(true)
                 .help(ColorKind::help(
-                    "Color for dead cells (omit if filter is not dye)",
+                    "Color for dead cells (omit if filter is not dye or half-dye)",
                     Some(ColorKind::Green),
                 )),
             Arg::new("color-alive")
@@ -65,7 +146,7 @@ static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
                 .hide_default_value(true)
                 .hide_possible_values(true)
                 .help(ColorKind::help(
-                    "Color for alive cells (omit if filter is not dye)",
+                    "Color for alive cells (omit if filter is not dye or half-dye)",
                     Some(ColorKind::White),
                 )),
             Arg::new("fps-max")
@@ -74,22 +155,120 @@ static MATCHES: LazyLock<ArgMatches> = LazyLock::new(|| {
                 .value_parser(value_parser!(f64))
                 .default_value("60.0")
                 .help("Maximum fps (use default if out of range [0.0, inf))"),
+            Arg::new("save-on-exit")
+                .long("save-on-exit")
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help("Save the world on exit (RLE, or plaintext for `.cells`, Life 1.06 for `.lif`)"),
             Arg::new("show-stats")
                 .long("show-stats")
                 .action(ArgAction::SetTrue)
                 .help("Show statistics"),
+            Arg::new("full-redraw")
+                .long("full-redraw")
+                .action(ArgAction::SetTrue)
+                .help("Redraw every cell on every frame, rather than only the changed ones"),
+            Arg::new("follow")
+                .long("follow")
+                .action(ArgAction::SetTrue)
+                .help("Keep the view centered on the bounding box of the alive cells"),
+            Arg::new("history")
+                .long("history")
+                .value_name("FRAMES")
+                .value_parser(value_parser!(usize))
+                .default_value("256")
+                .help("Number of frames to keep for rewinding, within 256 MiB (0 to disable)"),
+            Arg::new("on-stable")
+                .long("on-stable")
+                .value_name("ACTION")
+                .value_parser(value_parser!(OnStable))
+                .hide_possible_values(true)
+                .help(OnStable::help(
+                    "Action once the world repeats itself [default: none] \
+                     (headless runs stop in any case)",
+                    None,
+                )),
+            Arg::new("bind")
+                .long("bind")
+                .value_name("KEY=ACTION")
+                .value_parser(value_parser!(Binding))
+                .action(ArgAction::Append)
+                .allow_hyphen_values(true)
+                .help(bind_help()),
+            Arg::new("headless")
+                .long("headless")
+                .action(ArgAction::SetTrue)
+                .help("Run without a terminal and print the final statistics"),
+            Arg::new("generations")
+                .long("generations")
+                .value_name("INTEGER")
+                .value_parser(value_parser!(usize))
+                .help("Number of generations to evolve in headless mode"),
+            Arg::new("config")
+                .long("config")
+                .value_name("FILE")
+                .value_parser(value_parser!(PathBuf))
+                .help(
+                    "Configuration file of default options, one `KEY = VALUE` per line \
+                     with `KEY` the long name of an option \
+                     [default: $XDG_CONFIG_HOME/lifegame/config if present]",
+                ),
+            Arg::new("profile")
+                .long("profile")
+                .value_name("NAME")
+                .help("Also apply the options under `[NAME]` in the configuration file"),
         ])
-        .get_matches()
+        .configure(&CONFIG)
+        .quit_on_error();
+    let matches = command.get_matches_mut();
+    command.check_requirements(&matches);
+    matches
 });
 
+/// Options of the configuration file that apply.
+///
+/// They are read before the command line is parsed, as they make up the
+/// defaults of its options.
+static CONFIG: LazyLock<Vec<Entry>> = LazyLock::new(|| load_config().quit_on_error());
+
+/// Options that only make sense on the command line.
+const CONFIG_EXCLUDED: [&str; 2] = ["config", "profile"];
+
+/// Options along with those they require.
+///
+/// They are checked once parsed rather than declared with `requires`, which
+/// does not count options set by the configuration.
+const REQUIREMENTS: [(&str, &str); 3] = [
+    ("offset", "pattern"),
+    ("headless", "generations"),
+    ("generations", "headless"),
+];
+
 pub struct Args {
     pub nrows: usize,
     pub ncols: usize,
+    pub fit: bool,
     pub seed: Option<&'static str>,
     pub density: Density,
+    pub pattern: Option<&'static PathBuf>,
+    pub offset: Option<Offset>,
+    pub rule: Option<Rule>,
+    pub topology: Option<Topology>,
+    pub engine: EngineKind,
+    pub threads: NonZeroUsize,
+    pub memory_max: usize,
+    pub step: usize,
+    pub jump: usize,
     pub filter: Box<dyn Filter>,
     pub fps_max: FpsMax,
+    pub save_on_exit: Option<PathBuf>,
     pub show_stats: bool,
+    pub follow: bool,
+    pub full_redraw: bool,
+    pub history: usize,
+    pub on_stable: Option<OnStable>,
+    pub bindings: Vec<Binding>,
+    pub headless: Option<usize>,
 }
 
 impl Args {
@@ -102,11 +281,37 @@ impl Args {
             .get_one("ncols")
             .copied()
             .unwrap_or_else(|| unreachable!());
+        // A size on the command line takes over a fit from the configuration.
+        let fit = MATCHES.get_flag("fit")
+            && ["nrows", "ncols"]
+                .into_iter()
+                .all(|id| MATCHES.value_source(id) != Some(ValueSource::CommandLine));
         let seed = MATCHES.get_one("seed").map(String::as_ref);
         let density = MATCHES
             .get_one("density")
             .copied()
             .unwrap_or_else(|| unreachable!());
+        let pattern = MATCHES.get_one("pattern");
+        let offset = MATCHES.get_one("offset").copied();
+        let rule = MATCHES.get_one("rule").copied();
+        let topology = MATCHES.get_one("topology").copied();
+        let engine = MATCHES
+            .get_one("engine")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let threads = MATCHES.get_one("threads").copied();
+        let memory_max: usize = MATCHES
+            .get_one("memory-max")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let step: u32 = MATCHES
+            .get_one("step")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let jump = MATCHES
+            .get_one("jump")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
         let filter = MATCHES
             .get_one("filter")
             .copied()
@@ -115,30 +320,72 @@ impl Args {
             .get_one("fps-max")
             .copied()
             .unwrap_or_else(|| unreachable!());
+        let save_on_exit = MATCHES.get_one("save-on-exit").cloned();
         let show_stats = MATCHES.get_flag("show-stats");
+        let follow = MATCHES.get_flag("follow");
+        let full_redraw = MATCHES.get_flag("full-redraw");
+        let history = MATCHES
+            .get_one("history")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let on_stable = MATCHES.get_one("on-stable").copied();
+        let bindings: Vec<Binding> = MATCHES
+            .get_many("bind")
+            .unwrap_or_default()
+            .copied()
+            .collect();
+        let headless = MATCHES
+            .get_flag("headless")
+            .then(|| MATCHES.get_one("generations").copied())
+            .flatten();
 
         let density = Density::new(density)
             .or(Density::new(0.5))
             .unwrap_or_else(|| unreachable!());
+        let color_dead: ColorKind = MATCHES
+            .get_one("color-dead")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let color_alive: ColorKind = MATCHES
+            .get_one("color-alive")
+            .copied()
+            .unwrap_or_else(|| unreachable!());
+        let (color_dead, color_alive) = (color_dead.into(), color_alive.into());
         let filter: Box<dyn Filter> = match filter {
             FilterKind::Bit => Box::new(Bit),
             FilterKind::Block => Box::new(Block),
-            FilterKind::Dye => {
-                let color_dead = MATCHES
-                    .get_one::<ColorKind>("color-dead")
-                    .copied()
-                    .unwrap_or_else(|| unreachable!())
-                    .into();
-                let color_alive = MATCHES
-                    .get_one::<ColorKind>("color-alive")
-                    .copied()
-                    .unwrap_or_else(|| unreachable!())
-                    .into();
-                Box::new(Dye::new(color_dead, color_alive))
-            }
+            FilterKind::Dye => Box::new(Dye::new(color_dead, color_alive)),
             FilterKind::Emoji => Box::new(Emoji::random()),
+            FilterKind::Half => Box::new(Half::plain()),
+            FilterKind::HalfDye => Box::new(Half::dyed(color_dead, color_alive)),
+            FilterKind::Braille => Box::new(Braille),
             FilterKind::Hanzi => Box::new(Hanzi),
         };
+        let threads = threads
+            .or(thread::available_parallelism().ok())
+            .unwrap_or(NonZeroUsize::MIN);
+        let memory_max = memory_max.saturating_mul(1 << 20);
+        let step = 1 << step;
+        // Bindings add up rather than replace each other, except that the
+        // last binding of a key in the configuration takes over the earlier
+        // ones, and those on the command line take over all of them.
+        let configured: Vec<Binding> = CONFIG
+            .iter()
+            .filter(|entry| entry.key == "bind")
+            .map(|entry| entry.value.parse().unwrap_or_else(|_| unreachable!()))
+            .collect();
+        let bindings = configured
+            .iter()
+            .enumerate()
+            .filter(|&(index, binding)| {
+                configured[index + 1..]
+                    .iter()
+                    .chain(&bindings)
+                    .all(|other| other.key != binding.key)
+            })
+            .map(|(_, &binding)| binding)
+            .chain(bindings.iter().copied())
+            .collect();
         let fps_max = FpsMax::new(fps_max)
             .or(FpsMax::new(60.0))
             .unwrap_or_else(|| unreachable!());
@@ -146,11 +393,28 @@ impl Args {
         Self {
             nrows,
             ncols,
+            fit,
             seed,
             density,
+            pattern,
+            offset,
+            rule,
+            topology,
+            engine,
+            threads,
+            memory_max,
+            step,
+            jump,
             filter,
             fps_max,
+            save_on_exit,
             show_stats,
+            follow,
+            full_redraw,
+            history,
+            on_stable,
+            bindings,
+            headless,
         }
     }
 }
@@ -162,6 +426,9 @@ pub enum FilterKind {
     Dye,
     Emoji,
     Hanzi,
+    Half,
+    HalfDye,
+    Braille,
 }
 
 #[derive(Clone, Copy, Debug, ValueEnum)]
@@ -191,6 +458,157 @@ impl From<ColorKind> for Color {
     }
 }
 
+/// Reads the options of the configuration file given by `--config`, or else
+/// found in the configuration directory, along with those of `--profile`.
+fn load_config() -> Result<Vec<Entry>> {
+    let profile = raw_option("profile");
+    let config = match raw_option("config") {
+        Some(path) => Some(Config::load(path.as_ref())?),
+        None => match Config::default_path() {
+            Some(path) if path.is_file() => Some(Config::load(&path)?),
+            _ => None,
+        },
+    };
+    match config {
+        Some(config) => config.entries(profile.as_deref()),
+        None => match profile {
+            Some(profile) => bail!("no configuration file to find profile `{profile}` in"),
+            None => Ok(Vec::new()),
+        },
+    }
+}
+
+/// Returns the value of the long option `name` on the command line, ahead
+/// of parsing it in full.
+fn raw_option(name: &str) -> Option<String> {
+    let flag = format!("--{name}");
+    let mut args = env::args().skip(1).take_while(|arg| arg != "--");
+    while let Some(arg) = args.next() {
+        if arg == flag {
+            return args.next();
+        }
+        if let Some(value) = arg
+            .strip_prefix(&flag)
+            .and_then(|rest| rest.strip_prefix('='))
+        {
+            return Some(value.to_string());
+        }
+    }
+    None
+}
+
+trait CommandExt {
+    /// Makes the options of the configuration the defaults of the command,
+    /// after checking them as the command line would be.
+    fn configure(self, entries: &'static [Entry]) -> Result<Command>;
+
+    /// Exits with an error if an option is set, on the command line or in
+    /// the configuration, without another that it requires.
+    fn check_requirements(&mut self, matches: &ArgMatches);
+}
+
+impl CommandExt for Command {
+    fn configure(mut self, entries: &'static [Entry]) -> Result<Command> {
+        for entry in entries {
+            let Entry {
+                key,
+                value,
+                location,
+            } = entry;
+            let Some(arg) = self
+                .get_arguments()
+                .filter(|arg| !CONFIG_EXCLUDED.contains(&arg.get_id().as_str()))
+                .find(|arg| arg.get_long() == Some(key))
+            else {
+                bail!("{location}: unknown option `{key}`");
+            };
+
+            // Flags take `true` or `false`, and other options are checked by
+            // parsing them alone, which only fails on their values or on their
+            // relations to other options.
+            if let ArgAction::SetTrue = arg.get_action() {
+                if value.parse::<bool>().is_err() {
+                    bail!(
+                        "{location}: invalid value `{value}` for `{key}` (expected `true` or `false`)"
+                    );
+                }
+            } else if let Err(error) = self.clone().try_get_matches_from([
+                env!("CARGO_PKG_NAME").to_string(),
+                format!("--{key}={value}"),
+            ]) && let ErrorKind::InvalidValue | ErrorKind::ValueValidation = error.kind()
+            {
+                let possible_values: Vec<_> = arg
+                    .get_possible_values()
+                    .iter()
+                    .filter(|possible_value| !possible_value.is_hide_set())
+                    .map(|possible_value| format!("`{}`", possible_value.get_name()))
+                    .collect();
+                match error.source() {
+                    Some(source) => {
+                        bail!("{location}: invalid value `{value}` for `{key}`: {source}")
+                    }
+                    None if !possible_values.is_empty() => bail!(
+                        "{location}: invalid value `{value}` for `{key}` (expected one of {})",
+                        possible_values.join(", ")
+                    ),
+                    None => bail!("{location}: invalid value `{value}` for `{key}`"),
+                }
+            }
+
+            // Bindings add up, so they are merged with those of the command
+            // line later on.
+            if key != "bind" {
+                let id = arg.get_id().clone();
+                self = self.mut_arg(id, |arg| arg.default_value(value.as_str()));
+            }
+        }
+
+        Ok(self)
+    }
+
+    fn check_requirements(&mut self, matches: &ArgMatches) {
+        // Flags default to `false`, while the other options required have no
+        // default of their own and so are only present if set.
+        let is_set = |id: &str| match matches.try_get_one::<bool>(id) {
+            Ok(flag) => flag.copied().unwrap_or_default(),
+            Err(_) => matches.value_source(id).is_some(),
+        };
+        for (id, required) in REQUIREMENTS {
+            if !is_set(id) || is_set(required) {
+                continue;
+            }
+            let origin = match matches.value_source(id) {
+                Some(ValueSource::CommandLine) => String::new(),
+                _ => CONFIG
+                    .iter()
+                    .rfind(|entry| entry.key == id)
+                    .map(|entry| format!(" (set at {})", entry.location))
+                    .unwrap_or_default(),
+            };
+            self.error(
+                ErrorKind::MissingRequiredArgument,
+                format!("`--{id}`{origin} requires `--{required}`"),
+            )
+            .exit();
+        }
+    }
+}
+
+/// Returns the help of `--bind`, listing the default keys of each action.
+fn bind_help() -> String {
+    let mut message = String::from(
+        "Bind a key to an action, in place of its default (repeatable); keys are \
+         characters or names such as `left`, `esc`, `space` or `f5`, optionally \
+         prefixed by `ctrl-`, `alt-` or `shift-`, and cursor and toggle actions \
+         apply in edit mode",
+    );
+    for (action, keys) in Keymap::default().bindings() {
+        let keys: Vec<_> = keys.iter().map(ToString::to_string).collect();
+        message.push_str(&format!("\n- {action}: {}", keys.join(", ")));
+    }
+    message
+}
+
 trait ValueEnumExt: ValueEnum {
     fn help(description: &str, default: Option<Self>) -> String {
         let mut message = description.to_string();
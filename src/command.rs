@@ -0,0 +1,184 @@
+use crate::keymap::{Action, Key, Keymap};
+use crossterm::event::{Event, MouseButton, MouseEvent, MouseEventKind, read};
+use eoe::QuitOnError;
+use std::sync::mpsc::{self, Receiver};
+use std::thread;
+
+/// Action requested of the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Command {
+    /// Halve the speed.
+    SlowDown,
+
+    /// Double the speed.
+    SpeedUp,
+
+    TogglePause,
+
+    /// Pause, and evolve the given number of generations one per frame.
+    Step(usize),
+
+    /// Pause, and move through history by the given number of frames,
+    /// backward if negative.
+    Rewind(isize),
+
+    /// Flip random cells.
+    Flip,
+
+    /// Start over from the genesis.
+    Reset,
+
+    /// Save the world to a file.
+    Save,
+
+    /// Adapt to a new size of the terminal.
+    Resize,
+
+    /// Move the view by the given numbers of steps.
+    Pan {
+        rows: isize,
+        cols: isize,
+    },
+
+    /// Center the view on the population.
+    Center,
+
+    /// Enter edit mode and pause if `true`, or leave it and resume.
+    Edit(bool),
+
+    /// Move the edit cursor by the given numbers of cells.
+    MoveCursor {
+        rows: isize,
+        cols: isize,
+    },
+
+    /// Flip the cell under the edit cursor.
+    Toggle,
+
+    /// Paint with the mouse.
+    Stroke(Stroke),
+
+    /// Show or hide the key bindings.
+    Help,
+
+    Quit,
+}
+
+/// Mouse stroke over the screen, in terminal coordinates.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Stroke {
+    pub row: u16,
+    pub col: u16,
+    /// Whether the stroke starts here, rather than continues from a previous
+    /// location.
+    pub start: bool,
+}
+
+/// Spawns a thread that reads terminal events, and returns the commands they
+/// translate to under `keymap`.
+///
+/// The thread stops after sending [`Command::Quit`], or once the commands
+/// are no longer received.
+pub fn listen(keymap: Keymap) -> Receiver<Command> {
+    let (sender, receiver) = mpsc::channel();
+    thread::spawn(move || {
+        let mut listener = Listener::new(keymap);
+        loop {
+            let event = read().quit_on_error();
+            let Some(command) = listener.translate(event) else {
+                continue;
+            };
+            if sender.send(command).is_err() || command == Command::Quit {
+                break;
+            }
+        }
+    });
+    receiver
+}
+
+/// Translator of terminal events into commands.
+#[derive(Debug)]
+struct Listener {
+    keymap: Keymap,
+    /// Numeric prefix of the next key, as in `25n`.
+    count: Option<usize>,
+    editing: bool,
+}
+
+impl Listener {
+    fn new(keymap: Keymap) -> Self {
+        Self {
+            keymap,
+            count: None,
+            editing: false,
+        }
+    }
+
+    fn translate(&mut self, event: Event) -> Option<Command> {
+        let key_event = match event {
+            Event::Key(key_event) => key_event,
+            Event::Mouse(mouse_event) if self.editing => return stroke(mouse_event),
+            Event::Resize(..) => return Some(Command::Resize),
+            _ => return None,
+        };
+        if !key_event.is_press() {
+            return None;
+        }
+        let key = Key::from(key_event);
+
+        if let Some(digit) = key.digit() {
+            self.count = Some(
+                self.count
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(digit),
+            );
+            return None;
+        }
+        let count = self.count.take();
+        let frames = isize::try_from(count.unwrap_or(1)).unwrap_or(isize::MAX);
+
+        let command = match self.keymap.get(key, self.editing)? {
+            Action::SlowDown => Command::SlowDown,
+            Action::SpeedUp => Command::SpeedUp,
+            Action::Pause => Command::TogglePause,
+            Action::Step => Command::Step(count.unwrap_or(1)),
+            Action::Rewind => Command::Rewind(-frames),
+            Action::Replay => Command::Rewind(frames),
+            Action::Flip => Command::Flip,
+            Action::Reset => Command::Reset,
+            Action::Save => Command::Save,
+            Action::PanLeft => Command::Pan { rows: 0, cols: -1 },
+            Action::PanDown => Command::Pan { rows: 1, cols: 0 },
+            Action::PanUp => Command::Pan { rows: -1, cols: 0 },
+            Action::PanRight => Command::Pan { rows: 0, cols: 1 },
+            Action::Center => Command::Center,
+            Action::Edit => {
+                self.editing = !self.editing;
+                Command::Edit(self.editing)
+            }
+            Action::Help => Command::Help,
+            Action::Quit => Command::Quit,
+            Action::CursorLeft => Command::MoveCursor { rows: 0, cols: -1 },
+            Action::CursorDown => Command::MoveCursor { rows: 1, cols: 0 },
+            Action::CursorUp => Command::MoveCursor { rows: -1, cols: 0 },
+            Action::CursorRight => Command::MoveCursor { rows: 0, cols: 1 },
+            Action::Toggle => Command::Toggle,
+        };
+        Some(command)
+    }
+}
+
+/// Translates a press or a drag of the left mouse button into a stroke.
+fn stroke(event: MouseEvent) -> Option<Command> {
+    let start = match event.kind {
+        MouseEventKind::Down(MouseButton::Left) => true,
+        MouseEventKind::Drag(MouseButton::Left) => false,
+        _ => return None,
+    };
+    Some(Command::Stroke(Stroke {
+        row: event.row,
+        col: event.column,
+        start,
+    }))
+}
@@ -0,0 +1,202 @@
+use anyhow::{Context, Result, bail};
+use std::fmt::{self, Display, Formatter};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::{env, fs};
+
+/// Options read from a configuration file.
+///
+/// Each line is either a comment starting with `#`, an option in the form
+/// `KEY = VALUE` where `KEY` is the long name of a command-line option, or a
+/// `[PROFILE]` header after which options only apply to that profile.
+#[derive(Debug, Default)]
+pub struct Config {
+    /// Options outside of any profile.
+    common: Vec<Entry>,
+    /// Options of each profile, in the order the profiles appear.
+    profiles: Vec<(String, Vec<Entry>)>,
+}
+
+/// Option read from a configuration file.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub key: String,
+    pub value: String,
+    pub location: Location,
+}
+
+/// Line of a configuration file.
+#[derive(Debug, Clone)]
+pub struct Location {
+    path: Arc<Path>,
+    /// Line number, starting from one.
+    line: usize,
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}", self.path.display(), self.line)
+    }
+}
+
+impl Config {
+    /// Returns the path of the configuration file in the configuration
+    /// directory, as given by `XDG_CONFIG_HOME` or else `~/.config`.
+    pub fn default_path() -> Option<PathBuf> {
+        let directory = env::var_os("XDG_CONFIG_HOME")
+            .map(PathBuf::from)
+            .filter(|directory| directory.is_absolute())
+            .or_else(|| env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+        Some(directory.join("lifegame").join("config"))
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read configuration file `{}`", path.display()))?;
+        Self::parse(path, &text)
+    }
+
+    pub fn parse(path: &Path, text: &str) -> Result<Self> {
+        let path: Arc<Path> = Arc::from(path);
+        let mut config = Self::default();
+
+        for (index, line) in text.lines().enumerate() {
+            let location = Location {
+                path: Arc::clone(&path),
+                line: index + 1,
+            };
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            if let Some(header) = line.strip_prefix('[') {
+                let Some(name) = header.strip_suffix(']').map(str::trim) else {
+                    bail!("{location}: expected `]` at the end of the profile header");
+                };
+                if name.is_empty() {
+                    bail!("{location}: expected the name of a profile");
+                }
+                config.profiles.push((name.to_string(), Vec::new()));
+                continue;
+            }
+
+            let Some((key, value)) = line.split_once('=') else {
+                bail!("{location}: expected `KEY = VALUE` or `[PROFILE]`");
+            };
+            let key = key.trim();
+            if key.is_empty() {
+                bail!("{location}: expected the name of an option before `=`");
+            }
+            // Quotes keep the spaces around a value.
+            let value = value.trim();
+            let value = value
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .unwrap_or(value);
+
+            let entry = Entry {
+                key: key.to_string(),
+                value: value.to_string(),
+                location,
+            };
+            match config.profiles.last_mut() {
+                Some((_, entries)) => entries.push(entry),
+                None => config.common.push(entry),
+            }
+        }
+
+        Ok(config)
+    }
+
+    /// Returns the options that apply, those of `profile` last so that they
+    /// take precedence.
+    pub fn entries(&self, profile: Option<&str>) -> Result<Vec<Entry>> {
+        let mut entries = self.common.clone();
+        if let Some(profile) = profile {
+            let mut found = false;
+            for (name, profile_entries) in &self.profiles {
+                if name == profile {
+                    entries.extend_from_slice(profile_entries);
+                    found = true;
+                }
+            }
+            if !found {
+                bail!("no profile named `{profile}` in the configuration file");
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Config> {
+        Config::parse(Path::new("config"), text)
+    }
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text).unwrap_err())
+    }
+
+    fn pairs(entries: &[Entry]) -> Vec<(&str, &str)> {
+        entries
+            .iter()
+            .map(|entry| (entry.key.as_str(), entry.value.as_str()))
+            .collect()
+    }
+
+    #[test]
+    fn parses_options_and_profiles() {
+        let text = "\
+# Comment
+engine = packed
+bind = \" =pause\"
+
+[big]
+width=2000
+engine = hashlife
+[small]
+  width = 20  
+[big]
+rule = B36/S23
+";
+        let config = parse(text).unwrap();
+        let common = config.entries(None).unwrap();
+        assert_eq!(pairs(&common), [("engine", "packed"), ("bind", " =pause")]);
+        assert_eq!(common[1].location.to_string(), "config:3");
+
+        let big = config.entries(Some("big")).unwrap();
+        assert_eq!(
+            pairs(&big),
+            [
+                ("engine", "packed"),
+                ("bind", " =pause"),
+                ("width", "2000"),
+                ("engine", "hashlife"),
+                ("rule", "B36/S23"),
+            ]
+        );
+        assert_eq!(big[4].location.to_string(), "config:11");
+
+        let small = config.entries(Some("small")).unwrap();
+        assert_eq!(small.last().map(|entry| entry.value.as_str()), Some("20"));
+    }
+
+    #[test]
+    fn rejects_unknown_profiles() {
+        let config = parse("[big]\nwidth = 2000\n").unwrap();
+        let message = format!("{:#}", config.entries(Some("huge")).unwrap_err());
+        assert!(message.contains("no profile named `huge`"));
+    }
+
+    #[test]
+    fn rejects_malformed_lines() {
+        assert!(error("\n[big\n").contains("config:2: expected `]`"));
+        assert!(error("[ ]").contains("config:1: expected the name of a profile"));
+        assert!(error("# Comment\nwidth 20").contains("config:2: expected `KEY = VALUE`"));
+        assert!(error("= 20").contains("config:1: expected the name of an option"));
+    }
+}
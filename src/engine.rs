@@ -0,0 +1,333 @@
+pub use hashlife::HashLife;
+pub use naive::Naive;
+pub use packed::Packed;
+pub use sparse::Sparse;
+
+use crate::biosquare::{Cell, Changes, Rect};
+use crate::rule::Rule;
+use crate::topology::Topology;
+use anyhow::{Result, ensure};
+use clap::ValueEnum;
+use matreex::{Index, Matrix, Shape};
+use std::fmt::Debug;
+use std::num::NonZeroUsize;
+use std::panic;
+use std::thread;
+
+mod hashlife;
+mod naive;
+mod packed;
+mod sparse;
+
+/// Maximum area copied from an infinite world, whose bounding box may span
+/// far more cells than could ever be allocated.
+const SNAPSHOT_AREA_MAX: usize = 1 << 30;
+
+/// Offsets of the eight neighbors of a cell.
+const NEIGHBORS: [(isize, isize); 8] = [
+    (-1, -1),
+    (-1, 0),
+    (-1, 1),
+    (0, -1),
+    (0, 1),
+    (1, -1),
+    (1, 0),
+    (1, 1),
+];
+
+/// Algorithm that evolves the world.
+pub trait Engine: Debug {
+    fn rule(&self) -> Rule;
+
+    fn topology(&self) -> Topology;
+
+    /// Returns the shape of the world, or of the genesis if it is infinite.
+    fn shape(&self) -> Shape;
+
+    fn population(&self) -> usize;
+
+    /// Returns the numbers of cells born and died in the last generation
+    /// evolved, or [`None`] if the engine does not keep track of them.
+    ///
+    /// Cells changed by hand do not count.
+    fn changes(&self) -> Option<Changes>;
+
+    /// Returns the numbers of cells born and died over all generations
+    /// evolved, or [`None`] if the engine does not keep track of them.
+    fn total_changes(&self) -> Option<Changes>;
+
+    /// Replaces the numbers of cells born and died in the last generation
+    /// and over all generations, as when moving to another state in history.
+    ///
+    /// Engines that do not keep track of them ignore this.
+    fn set_changes(&mut self, changes: Changes, total_changes: Changes);
+
+    /// Returns the locations of the cells flipped by the last generation
+    /// evolved, each once, or [`None`] if the engine does not keep track of
+    /// them.
+    ///
+    /// Cells changed by hand do not count.
+    fn flips(&self) -> Option<&[(isize, isize)]>;
+
+    /// Returns the cell at the given location, relative to the top-left
+    /// corner of the genesis.
+    ///
+    /// Cells beyond a bounded world are always dead.
+    fn get(&self, row: isize, col: isize) -> Cell;
+
+    /// Replaces the cell at the given location, relative to the top-left
+    /// corner of the genesis.
+    ///
+    /// Cells beyond a bounded world are left untouched.
+    fn set(&mut self, row: isize, col: isize, cell: Cell);
+
+    /// Returns the smallest region containing all alive cells, or [`None`]
+    /// if there are none.
+    fn bounding_box(&self) -> Option<Rect>;
+
+    /// Returns a hash of the alive cells, which is the same for equal states
+    /// of the world.
+    ///
+    /// An infinite world is hashed along with the location of its cells, so
+    /// that states only translated from each other differ.
+    fn digest(&self) -> u64;
+
+    /// Evolves the world by one generation.
+    fn evolve(&mut self);
+
+    /// Evolves the world by the given number of generations.
+    fn advance(&mut self, generations: usize) {
+        for _ in 0..generations {
+            self.evolve();
+        }
+    }
+
+    /// Returns the locations of the alive cells, row by row.
+    fn alive(&self) -> Vec<(isize, isize)>;
+
+    /// Returns a copy of the whole world, or of its bounding box if it is
+    /// infinite.
+    ///
+    /// Returns an error if the bounding box is too large to be copied.
+    fn snapshot(&self) -> Result<Matrix<Cell>> {
+        let rect = match self.topology() {
+            Topology::Infinite => self.bounding_box().unwrap_or_default(),
+            _ => Rect::new(0, 0, self.shape()),
+        };
+        ensure!(
+            self.topology() != Topology::Infinite
+                || rect.nrows.saturating_mul(rect.ncols) <= SNAPSHOT_AREA_MAX,
+            "bounding box of {}×{} cells is too large to be copied",
+            rect.nrows,
+            rect.ncols,
+        );
+        let cells = Matrix::from_fn(rect.shape(), |index| {
+            let (row, col) = rect.location(index);
+            self.get(row, col)
+        })?;
+        Ok(cells)
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Engine>;
+}
+
+impl Clone for Box<dyn Engine> {
+    fn clone(&self) -> Self {
+        self.boxed_clone()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum EngineKind {
+    /// One cell at a time.
+    #[default]
+    Naive,
+
+    /// 64 cells at a time, packed into machine words.
+    Packed,
+
+    /// Memoized quadtree on an infinite plane, for very long runs.
+    #[value(name = "hashlife")]
+    HashLife,
+
+    /// Set of alive cells on an infinite plane, for sparse patterns.
+    Sparse,
+}
+
+impl EngineKind {
+    /// Returns the topology the engine is designed for.
+    pub fn topology(self) -> Topology {
+        match self {
+            Self::Naive | Self::Packed => Topology::Torus,
+            Self::HashLife | Self::Sparse => Topology::Infinite,
+        }
+    }
+
+    /// Builds an engine for the given world.
+    ///
+    /// `threads` only applies to bounded engines, and `memory_max` (in bytes)
+    /// only applies to [`HashLife`].
+    pub fn build(
+        self,
+        genesis: Matrix<Cell>,
+        rule: Rule,
+        topology: Topology,
+        threads: NonZeroUsize,
+        memory_max: usize,
+    ) -> Result<Box<dyn Engine>> {
+        let engine: Box<dyn Engine> = match self {
+            Self::Naive => Box::new(Naive::new(genesis, rule, topology, threads)?),
+            Self::Packed => Box::new(Packed::new(genesis, rule, topology, threads)?),
+            Self::HashLife => Box::new(HashLife::new(genesis, rule, topology, memory_max)?),
+            Self::Sparse => Box::new(Sparse::new(genesis, rule, topology)?),
+        };
+        Ok(engine)
+    }
+}
+
+/// Returns the index of the given location within a bounded world of the
+/// given shape, or [`None`] if it lies beyond.
+fn locate(shape: Shape, row: isize, col: isize) -> Option<Index> {
+    let row = usize::try_from(row).ok().filter(|&row| row < shape.nrows)?;
+    let col = usize::try_from(col).ok().filter(|&col| col < shape.ncols)?;
+    Some(Index::new(row, col))
+}
+
+/// Splits `next`, the row-major storage of the next generation, into bands of
+/// whole rows and fills them in parallel on up to `threads` threads.
+///
+/// `evolve` is called with the index of the first row of a band and the band
+/// itself. Its results are returned in the order of the bands, so that the
+/// outcome does not depend on the number of threads.
+fn in_bands<T, R, F>(next: &mut [T], shape: Shape, threads: NonZeroUsize, evolve: F) -> Vec<R>
+where
+    T: Send,
+    R: Send,
+    F: Fn(usize, &mut [T]) -> R + Sync,
+{
+    // Spawning a thread costs about as much as evolving this many cells on
+    // it, so smaller bands are not worth it.
+    const MIN_CELLS_PER_BAND: usize = 1 << 14;
+
+    let Shape { nrows, ncols } = shape;
+    if nrows == 0 {
+        return Vec::new();
+    }
+
+    let bands = (nrows * ncols / MIN_CELLS_PER_BAND).clamp(1, threads.get().min(nrows));
+    if bands == 1 {
+        return vec![evolve(0, next)];
+    }
+
+    let rows_per_band = nrows.div_ceil(bands);
+    let row_len = next.len() / nrows;
+    let evolve = &evolve;
+
+    thread::scope(|scope| {
+        next.chunks_mut(rows_per_band * row_len)
+            .enumerate()
+            .map(|(band, next)| scope.spawn(move || evolve(band * rows_per_band, next)))
+            .collect::<Vec<_>>()
+            .into_iter()
+            .map(|handle| {
+                handle
+                    .join()
+                    .unwrap_or_else(|error| panic::resume_unwind(error))
+            })
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::genesis::{Density, Genesis};
+
+    /// The packed engine must evolve exactly like the naive one, whatever
+    /// the topology, the rule, and the width relative to a machine word.
+    #[test]
+    fn packed_matches_naive() {
+        let shapes = [
+            Shape::new(1, 1),
+            Shape::new(2, 3),
+            Shape::new(17, 64),
+            Shape::new(23, 70),
+            Shape::new(9, 130),
+        ];
+        let rules = ["B3/S23", "B36/S23", "B2/S", "B1357/S1357", "B0/S8"];
+        let threads = NonZeroUsize::new(3).unwrap();
+        let density = Density::new(0.4).unwrap();
+
+        let topologies = Topology::value_variants()
+            .iter()
+            .filter(|topology| topology.edges().is_some());
+        for &topology in topologies {
+            for rule in rules {
+                for shape in shapes {
+                    let rule: Rule = rule.parse().unwrap();
+                    let genesis = Genesis::new(shape).random(density, Some(rule)).unwrap();
+                    let mut naive = Naive::new(genesis.clone(), rule, topology, threads).unwrap();
+                    let mut packed = Packed::new(genesis, rule, topology, threads).unwrap();
+
+                    for generation in 1..=16 {
+                        naive.evolve();
+                        packed.evolve();
+                        let context = format!("{topology} {rule} {shape:?} at {generation}");
+                        assert_eq!(
+                            naive.snapshot().unwrap(),
+                            packed.snapshot().unwrap(),
+                            "{context}"
+                        );
+                        assert_eq!(naive.alive(), packed.alive(), "{context}");
+                        assert_eq!(naive.population(), packed.population(), "{context}");
+                        assert_eq!(naive.changes(), packed.changes(), "{context}");
+                        assert_eq!(naive.flips(), packed.flips(), "{context}");
+                        assert_eq!(naive.bounding_box(), packed.bounding_box(), "{context}");
+                    }
+                }
+            }
+        }
+    }
+
+    /// Splitting the world into bands on several threads must not change
+    /// the outcome of either bounded engine.
+    #[test]
+    fn bands_match_single_thread() {
+        // Large enough to be split into three bands.
+        let shape = Shape::new(130, 400);
+        let rule: Rule = "B36/S23".parse().unwrap();
+        let density = Density::new(0.4).unwrap();
+        assert!(shape.nrows * shape.ncols >= 3 * (1 << 14));
+
+        let topologies = Topology::value_variants()
+            .iter()
+            .filter(|topology| topology.edges().is_some());
+        for &topology in topologies {
+            let genesis = Genesis::new(shape).random(density, Some(rule)).unwrap();
+            let build = |kind: EngineKind, threads| {
+                let threads = NonZeroUsize::new(threads).unwrap();
+                kind.build(genesis.clone(), rule, topology, threads, 0)
+                    .unwrap()
+            };
+            let mut expected = build(EngineKind::Naive, 1);
+            let mut engines = [
+                ("naive", build(EngineKind::Naive, 4)),
+                ("packed", build(EngineKind::Packed, 1)),
+                ("packed", build(EngineKind::Packed, 4)),
+            ];
+
+            for generation in 1..=8 {
+                expected.evolve();
+                let snapshot = expected.snapshot().unwrap();
+                for (name, engine) in &mut engines {
+                    engine.evolve();
+                    let context = format!("{name} {topology} at {generation}");
+                    assert_eq!(engine.snapshot().unwrap(), snapshot, "{context}");
+                    assert_eq!(engine.population(), expected.population(), "{context}");
+                    assert_eq!(engine.changes(), expected.changes(), "{context}");
+                    assert_eq!(engine.flips(), expected.flips(), "{context}");
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,718 @@
+use super::Engine;
+use crate::biosquare::{Cell, Changes, Rect};
+use crate::rule::Rule;
+use crate::topology::Topology;
+use anyhow::{Result, ensure};
+use matreex::{Index, Matrix, Shape};
+use std::collections::HashMap;
+use std::mem;
+
+type NodeId = u32;
+
+const DEAD: NodeId = 0;
+const ALIVE: NodeId = 1;
+
+/// Engine implementing Gosper's [HashLife] algorithm on an infinite plane.
+///
+/// The plane is represented by a quadtree whose identical subtrees are
+/// shared, and the evolution of each subtree is memoized, so that regular
+/// patterns can be advanced by huge numbers of generations at once.
+///
+/// The plane grows as needed, so that cells beyond the genesis keep evolving.
+///
+/// [HashLife]: https://conwaylife.com/wiki/HashLife
+#[derive(Debug)]
+pub struct HashLife {
+    rule: Rule,
+    shape: Shape,
+    memory_max: usize,
+    nodes: Vec<Node>,
+    interned: HashMap<[NodeId; 4], NodeId>,
+    results: HashMap<(NodeId, u8), NodeId>,
+    empty: Vec<NodeId>,
+    root: NodeId,
+}
+
+/// A square of `2^level × 2^level` cells, made of four quadrants in the order
+/// north-west, north-east, south-west and south-east.
+///
+/// Leaves (level 0) are single cells and have no quadrants.
+#[derive(Debug, Clone, Copy)]
+struct Node {
+    level: u8,
+    quadrants: [NodeId; 4],
+    population: u64,
+    /// Hash of the cells, which unlike the id of the node does not change
+    /// when the node cache is collected.
+    hash: u64,
+}
+
+impl HashLife {
+    /// Smallest level of the root, i.e. an `8×8` square.
+    const LEVEL_MIN: u8 = 3;
+
+    /// Largest level of the root, so that coordinates fit in an `i64`.
+    const LEVEL_MAX: u8 = 62;
+
+    /// Largest `j` for which a step of `2^j` generations fits in the root of
+    /// the largest level.
+    const STEP_MAX: u8 = Self::LEVEL_MAX - 2;
+
+    pub fn new(
+        genesis: Matrix<Cell>,
+        rule: Rule,
+        topology: Topology,
+        memory_max: usize,
+    ) -> Result<Self> {
+        ensure!(
+            topology == Topology::Infinite,
+            "the hashlife engine does not support the {topology} topology"
+        );
+        // Births from no neighbors would fill the infinite plane at once.
+        ensure!(
+            !rule.birth(0),
+            "the hashlife engine does not support rules with birth on 0 neighbors"
+        );
+
+        let shape = genesis.shape();
+        let leaf = |population| Node {
+            level: 0,
+            quadrants: [DEAD; 4],
+            population,
+            hash: population,
+        };
+
+        let mut hashlife = Self {
+            rule,
+            shape,
+            memory_max,
+            nodes: vec![leaf(0), leaf(1)],
+            interned: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+        };
+
+        let extent = shape.nrows.max(shape.ncols).max(1);
+        let mut level = Self::LEVEL_MIN;
+        while 1 << (level - 1) < extent {
+            level += 1;
+        }
+        let half = 1 << (level - 1);
+        hashlife.root = hashlife.build(&genesis, level, -half, -half);
+
+        Ok(hashlife)
+    }
+
+    fn node(&self, id: NodeId) -> Node {
+        self.nodes[id as usize]
+    }
+
+    fn level(&self) -> u8 {
+        self.node(self.root).level
+    }
+
+    /// Returns the unique node with the given quadrants.
+    fn join(&mut self, quadrants: [NodeId; 4]) -> NodeId {
+        if let Some(&id) = self.interned.get(&quadrants) {
+            return id;
+        }
+
+        let level = self.node(quadrants[0]).level + 1;
+        let population = quadrants.iter().map(|&id| self.node(id).population).sum();
+        let hash = hash(level, quadrants.map(|id| self.node(id).hash));
+        let id = NodeId::try_from(self.nodes.len()).expect("too many hashlife nodes");
+        self.nodes.push(Node {
+            level,
+            quadrants,
+            population,
+            hash,
+        });
+        self.interned.insert(quadrants, id);
+        id
+    }
+
+    fn empty(&mut self, level: u8) -> NodeId {
+        while self.empty.len() <= level as usize {
+            let below = self.empty[self.empty.len() - 1];
+            let id = self.join([below; 4]);
+            self.empty.push(id);
+        }
+        self.empty[level as usize]
+    }
+
+    /// Builds the node of the given level whose top-left corner lies at
+    /// (`top`, `left`), relative to the top-left corner of `genesis`.
+    fn build(&mut self, genesis: &Matrix<Cell>, level: u8, top: i64, left: i64) -> NodeId {
+        let size = 1i64 << level;
+        let nrows = genesis.nrows() as i64;
+        let ncols = genesis.ncols() as i64;
+        if top >= nrows || left >= ncols || top + size <= 0 || left + size <= 0 {
+            return self.empty(level);
+        }
+        if level == 0 {
+            let index = Index::new(top as usize, left as usize);
+            return match genesis[index] {
+                Cell::Dead => DEAD,
+                Cell::Alive => ALIVE,
+            };
+        }
+
+        let half = size / 2;
+        let quadrants = [
+            self.build(genesis, level - 1, top, left),
+            self.build(genesis, level - 1, top, left + half),
+            self.build(genesis, level - 1, top + half, left),
+            self.build(genesis, level - 1, top + half, left + half),
+        ];
+        self.join(quadrants)
+    }
+
+    /// Returns the central quadrant of a node, i.e. the node of one level
+    /// below centered within it.
+    fn center(&mut self, id: NodeId) -> NodeId {
+        let [nw, ne, sw, se] = self.node(id).quadrants;
+        let quadrants = [
+            self.node(nw).quadrants[3],
+            self.node(ne).quadrants[2],
+            self.node(sw).quadrants[1],
+            self.node(se).quadrants[0],
+        ];
+        self.join(quadrants)
+    }
+
+    /// Returns the central quadrant of a node of level `k` after `2^step`
+    /// generations, where `step <= k - 2`.
+    fn successor(&mut self, id: NodeId, step: u8) -> NodeId {
+        let node = self.node(id);
+        if node.population == 0 {
+            return self.empty(node.level - 1);
+        }
+        if node.level == 2 {
+            return self.successor_base(id);
+        }
+        if let Some(&result) = self.results.get(&(id, step)) {
+            return result;
+        }
+
+        // Nine overlapping nodes of level `k - 1`, in row-major order.
+        let [nw, ne, sw, se] = node.quadrants;
+        let [_, nw_ne, nw_sw, nw_se] = self.node(nw).quadrants;
+        let [ne_nw, _, ne_sw, ne_se] = self.node(ne).quadrants;
+        let [sw_nw, sw_ne, _, sw_se] = self.node(sw).quadrants;
+        let [se_nw, se_ne, se_sw, _] = self.node(se).quadrants;
+        let nine = [
+            nw,
+            self.join([nw_ne, ne_nw, nw_se, ne_sw]),
+            ne,
+            self.join([nw_sw, nw_se, sw_nw, sw_ne]),
+            self.join([nw_se, ne_sw, sw_ne, se_nw]),
+            self.join([ne_sw, ne_se, se_nw, se_ne]),
+            sw,
+            self.join([sw_ne, se_nw, sw_se, se_sw]),
+            se,
+        ];
+
+        // At full speed, both halves of the step advance `2^(k - 3)`
+        // generations. Otherwise, only the second half advances.
+        let full = step == node.level - 2;
+        let nine = nine.map(|id| {
+            if full {
+                self.successor(id, step - 1)
+            } else {
+                self.center(id)
+            }
+        });
+        let remaining = if full { step - 1 } else { step };
+
+        let quadrants = [[0, 1, 3, 4], [1, 2, 4, 5], [3, 4, 6, 7], [4, 5, 7, 8]].map(|indices| {
+            let quadrant = self.join(indices.map(|index| nine[index]));
+            self.successor(quadrant, remaining)
+        });
+        let result = self.join(quadrants);
+
+        self.results.insert((id, step), result);
+        result
+    }
+
+    /// Returns the central `2×2` cells of a `4×4` node after one generation.
+    fn successor_base(&mut self, id: NodeId) -> NodeId {
+        let mut cells = [[false; 4]; 4];
+        for (quadrant, &child) in self.node(id).quadrants.iter().enumerate() {
+            for (offset, &leaf) in self.node(child).quadrants.iter().enumerate() {
+                let row = quadrant / 2 * 2 + offset / 2;
+                let col = quadrant % 2 * 2 + offset % 2;
+                cells[row][col] = leaf == ALIVE;
+            }
+        }
+
+        let quadrants = [(1, 1), (1, 2), (2, 1), (2, 2)].map(|(row, col)| {
+            let neighbors = (row - 1..=row + 1)
+                .flat_map(|r| (col - 1..=col + 1).map(move |c| (r, c)))
+                .filter(|&(r, c)| (r, c) != (row, col) && cells[r][c])
+                .count();
+            match self.rule.apply(Cell::from(cells[row][col]), neighbors) {
+                Cell::Dead => DEAD,
+                Cell::Alive => ALIVE,
+            }
+        });
+        self.join(quadrants)
+    }
+
+    /// Doubles the side of the root, keeping it centered at the origin.
+    fn expand(&mut self) {
+        let level = self.level();
+        let empty = self.empty(level - 1);
+        let [nw, ne, sw, se] = self.node(self.root).quadrants;
+        let quadrants = [
+            self.join([empty, empty, empty, nw]),
+            self.join([empty, empty, ne, empty]),
+            self.join([empty, sw, empty, empty]),
+            self.join([se, empty, empty, empty]),
+        ];
+        self.root = self.join(quadrants);
+    }
+
+    /// Advances the whole plane by `2^j` generations, where
+    /// `j <= STEP_MAX`.
+    ///
+    /// The node cache is only collected before the step, as collecting it
+    /// within [`successor`](Self::successor) would rename the nodes it is
+    /// still holding, so a single step may exceed `memory_max`.
+    fn step(&mut self, j: u8) {
+        if self.memory() > self.memory_max {
+            self.collect_garbage();
+        }
+
+        // The pattern must be confined to the center of the root, so that it
+        // cannot grow out of the result within `2^j` generations.
+        loop {
+            let root = self.root;
+            let level = self.level();
+            if level >= Self::LEVEL_MAX {
+                break;
+            }
+            let center = self.center(root);
+            if level >= j + 2 && self.node(center).population == self.node(root).population {
+                break;
+            }
+            self.expand();
+        }
+        self.expand();
+
+        self.root = self.successor(self.root, j);
+    }
+
+    /// Returns the approximate number of bytes used by the node cache.
+    fn memory(&self) -> usize {
+        self.nodes.capacity() * mem::size_of::<Node>()
+            + self.interned.capacity() * mem::size_of::<([NodeId; 4], NodeId)>()
+            + self.results.capacity() * mem::size_of::<((NodeId, u8), NodeId)>()
+    }
+
+    /// Drops every node that is not part of the current plane, along with
+    /// the memoized results involving them.
+    fn collect_garbage(&mut self) {
+        let leaves = self.nodes[..2].to_vec();
+        let nodes = mem::replace(&mut self.nodes, leaves);
+        let results = mem::take(&mut self.results);
+        self.interned = HashMap::new();
+        self.empty = vec![DEAD];
+
+        let mut renamed = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        self.root = self.copy(&nodes, self.root, &mut renamed);
+
+        self.results = results
+            .into_iter()
+            .filter_map(|((id, j), result)| Some(((*renamed.get(&id)?, j), *renamed.get(&result)?)))
+            .collect();
+        self.nodes.shrink_to_fit();
+        self.interned.shrink_to_fit();
+        self.results.shrink_to_fit();
+    }
+
+    fn copy(
+        &mut self,
+        nodes: &[Node],
+        id: NodeId,
+        renamed: &mut HashMap<NodeId, NodeId>,
+    ) -> NodeId {
+        if let Some(&id) = renamed.get(&id) {
+            return id;
+        }
+        let quadrants = nodes[id as usize]
+            .quadrants
+            .map(|quadrant| self.copy(nodes, quadrant, renamed));
+        let copied = self.join(quadrants);
+        renamed.insert(id, copied);
+        copied
+    }
+
+    /// Returns the location of the given cell relative to the top-left
+    /// corner of the root, or [`None`] if it lies beyond the root.
+    fn locate(&self, row: isize, col: isize) -> Option<(u64, u64)> {
+        let half = 1i64 << (self.level() - 1);
+        let row = i64::try_from(row).ok()?.checked_add(half)?;
+        let col = i64::try_from(col).ok()?.checked_add(half)?;
+        let size = 2 * half;
+        ((0..size).contains(&row) && (0..size).contains(&col)).then_some((row as u64, col as u64))
+    }
+
+    fn set_at(&mut self, id: NodeId, row: u64, col: u64, cell: Cell) -> NodeId {
+        let node = self.node(id);
+        if node.level == 0 {
+            return match cell {
+                Cell::Dead => DEAD,
+                Cell::Alive => ALIVE,
+            };
+        }
+
+        let half = 1 << (node.level - 1);
+        let quadrant = usize::from(row >= half) * 2 + usize::from(col >= half);
+        let mut quadrants = node.quadrants;
+        quadrants[quadrant] = self.set_at(quadrants[quadrant], row % half, col % half, cell);
+        self.join(quadrants)
+    }
+
+    /// Returns the distance from one side of a non-empty node to its nearest
+    /// alive cell.
+    ///
+    /// `near` are the two quadrants along that side and `far` the other two.
+    /// `depths` memoizes the results for the same side.
+    fn depth(
+        &self,
+        id: NodeId,
+        near: [usize; 2],
+        far: [usize; 2],
+        depths: &mut HashMap<NodeId, u64>,
+    ) -> u64 {
+        let node = self.node(id);
+        if node.level == 0 {
+            return 0;
+        }
+        if let Some(&depth) = depths.get(&id) {
+            return depth;
+        }
+
+        let nearest = |quadrants: [usize; 2], depths: &mut HashMap<NodeId, u64>| {
+            quadrants
+                .into_iter()
+                .map(|quadrant| node.quadrants[quadrant])
+                .filter(|&quadrant| self.node(quadrant).population > 0)
+                .map(|quadrant| self.depth(quadrant, near, far, depths))
+                .min()
+        };
+        let half = 1 << (node.level - 1);
+        let depth = match nearest(near, depths) {
+            Some(depth) => depth,
+            None => half + nearest(far, depths).unwrap_or_else(|| unreachable!()),
+        };
+
+        depths.insert(id, depth);
+        depth
+    }
+
+    /// Pushes the locations of the alive cells of a node whose top-left
+    /// corner lies at (`top`, `left`), relative to the origin.
+    fn push_alive(&self, id: NodeId, top: i64, left: i64, alive: &mut Vec<(isize, isize)>) {
+        let node = self.node(id);
+        if node.population == 0 {
+            return;
+        }
+        if node.level == 0 {
+            // Cells within the root fit in an `isize`, so the following casts
+            // are always safe.
+            alive.push((top as isize, left as isize));
+            return;
+        }
+
+        let half = 1 << (node.level - 1);
+        let [nw, ne, sw, se] = node.quadrants;
+        self.push_alive(nw, top, left, alive);
+        self.push_alive(ne, top, left + half, alive);
+        self.push_alive(sw, top + half, left, alive);
+        self.push_alive(se, top + half, left + half, alive);
+    }
+}
+
+impl Clone for HashLife {
+    /// Copies the plane, leaving out the nodes that are no longer part of it
+    /// and the memoized results.
+    fn clone(&self) -> Self {
+        let mut clone = Self {
+            rule: self.rule,
+            shape: self.shape,
+            memory_max: self.memory_max,
+            nodes: self.nodes[..2].to_vec(),
+            interned: HashMap::new(),
+            results: HashMap::new(),
+            empty: vec![DEAD],
+            root: DEAD,
+        };
+        let mut renamed = HashMap::from([(DEAD, DEAD), (ALIVE, ALIVE)]);
+        clone.root = clone.copy(&self.nodes, self.root, &mut renamed);
+        clone
+    }
+}
+
+impl Engine for HashLife {
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::Infinite
+    }
+
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    fn population(&self) -> usize {
+        self.node(self.root)
+            .population
+            .try_into()
+            .unwrap_or(usize::MAX)
+    }
+
+    fn changes(&self) -> Option<Changes> {
+        // Generations are evolved in bulk within nodes, without ever comparing
+        // one to the next cell by cell.
+        None
+    }
+
+    fn total_changes(&self) -> Option<Changes> {
+        None
+    }
+
+    fn set_changes(&mut self, _changes: Changes, _total_changes: Changes) {}
+
+    fn flips(&self) -> Option<&[(isize, isize)]> {
+        None
+    }
+
+    fn get(&self, row: isize, col: isize) -> Cell {
+        let Some((mut row, mut col)) = self.locate(row, col) else {
+            return Cell::Dead;
+        };
+
+        let mut node = self.node(self.root);
+        while node.level > 0 {
+            if node.population == 0 {
+                return Cell::Dead;
+            }
+            let half = 1 << (node.level - 1);
+            let quadrant = usize::from(row >= half) * 2 + usize::from(col >= half);
+            node = self.node(node.quadrants[quadrant]);
+            row %= half;
+            col %= half;
+        }
+        Cell::from(node.population == 1)
+    }
+
+    fn set(&mut self, row: isize, col: isize, cell: Cell) {
+        let (row, col) = loop {
+            match self.locate(row, col) {
+                Some(location) => break location,
+                None if self.level() < Self::LEVEL_MAX => self.expand(),
+                None => return,
+            }
+        };
+        self.root = self.set_at(self.root, row, col, cell);
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        if self.node(self.root).population == 0 {
+            return None;
+        }
+
+        let depth = |near, far| self.depth(self.root, near, far, &mut HashMap::new());
+        let top = depth([0, 1], [2, 3]);
+        let left = depth([0, 2], [1, 3]);
+        let bottom = depth([2, 3], [0, 1]);
+        let right = depth([1, 3], [0, 2]);
+
+        // The root is at most `2^LEVEL_MAX` cells wide, so the following
+        // casts are always safe.
+        let half = 1i64 << (self.level() - 1);
+        let size = 2 * half as u64;
+        Some(Rect {
+            top: (top as i64 - half) as isize,
+            left: (left as i64 - half) as isize,
+            nrows: (size - top - bottom) as usize,
+            ncols: (size - left - right) as usize,
+        })
+    }
+
+    fn alive(&self) -> Vec<(isize, isize)> {
+        let half = 1 << (self.level() - 1);
+        let mut alive = Vec::new();
+        self.push_alive(self.root, -half, -half, &mut alive);
+        alive.sort_unstable();
+        alive
+    }
+
+    /// Hashes the smallest square centered at the origin that contains all
+    /// alive cells, as the root is larger by however much the plane has
+    /// grown.
+    fn digest(&self) -> u64 {
+        let root = self.node(self.root);
+        let mut level = root.level;
+        let mut quadrants = root.quadrants;
+        while level > 1 {
+            let [nw, ne, sw, se] = quadrants;
+            let center = [
+                self.node(nw).quadrants[3],
+                self.node(ne).quadrants[2],
+                self.node(sw).quadrants[1],
+                self.node(se).quadrants[0],
+            ];
+            let population: u64 = center.iter().map(|&id| self.node(id).population).sum();
+            if population != root.population {
+                break;
+            }
+            level -= 1;
+            quadrants = center;
+        }
+        hash(level, quadrants.map(|id| self.node(id).hash))
+    }
+
+    fn evolve(&mut self) {
+        self.step(0);
+    }
+
+    /// Advances the world by decomposing `generations` into powers of two,
+    /// each taken in a single step, or in several of the largest ones if too
+    /// large for the root.
+    fn advance(&mut self, generations: usize) {
+        for j in 0..usize::BITS as u8 {
+            if generations >> j & 1 == 1 {
+                let (j, steps) = match j.checked_sub(Self::STEP_MAX) {
+                    Some(excess) => (Self::STEP_MAX, 1 << excess),
+                    None => (j, 1),
+                };
+                for _ in 0..steps {
+                    self.step(j);
+                }
+            }
+        }
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Engine> {
+        Box::new(self.clone())
+    }
+}
+
+/// Returns the hash of a node of the given level from the hashes of its
+/// quadrants, mixed with the finalizer of [SplitMix64].
+///
+/// [SplitMix64]: https://prng.di.unimi.it/splitmix64.c
+fn hash(level: u8, quadrants: [u64; 4]) -> u64 {
+    quadrants
+        .into_iter()
+        .fold(u64::from(level), |hash, quadrant| {
+            let mut z = (hash ^ quadrant).wrapping_add(0x9e37_79b9_7f4a_7c15);
+            z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+            z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+            z ^ (z >> 31)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::Sparse;
+    use crate::genesis::{Density, Genesis};
+
+    /// Jumping must land on the same world as evolving one generation at a
+    /// time, including when the node cache is collected before every step.
+    #[test]
+    fn jump_matches_sparse() {
+        let shape = Shape::new(16, 16);
+        let rules = ["B3/S23", "B36/S23", "B3678/S34678"];
+        let jumps = [1, 2, 7, 64, 100, 500];
+        let density = Density::new(0.4).unwrap();
+
+        for rule in rules {
+            for memory_max in [0, usize::MAX] {
+                let rule: Rule = rule.parse().unwrap();
+                let genesis = Genesis::new(shape).random(density, Some(rule)).unwrap();
+                let topology = Topology::Infinite;
+                let mut hashlife =
+                    HashLife::new(genesis.clone(), rule, topology, memory_max).unwrap();
+                let mut sparse = Sparse::new(genesis, rule, topology).unwrap();
+
+                let mut generation = 0;
+                for jump in jumps {
+                    hashlife.advance(jump);
+                    for _ in 0..jump {
+                        sparse.evolve();
+                    }
+                    generation += jump;
+
+                    let context = format!("{rule} with {memory_max} bytes at {generation}");
+                    assert_eq!(hashlife.alive(), sparse.alive(), "{context}");
+                    assert_eq!(hashlife.population(), sparse.population(), "{context}");
+                    assert_eq!(hashlife.bounding_box(), sparse.bounding_box(), "{context}");
+                    assert_eq!(
+                        hashlife.snapshot().unwrap(),
+                        sparse.snapshot().unwrap(),
+                        "{context}"
+                    );
+                }
+            }
+        }
+    }
+
+    /// Equal states must have the same digest however much the plane has
+    /// grown and whether the node cache was collected, unlike translated
+    /// ones.
+    #[test]
+    fn digest_follows_cells() {
+        let drawn = |rows: &[&str]| {
+            let ncols = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+            Matrix::from_fn(Shape::new(rows.len(), ncols), |index| {
+                Cell::from(rows[index.row].as_bytes()[index.col] == b'O')
+            })
+            .unwrap()
+        };
+        let topology = Topology::Infinite;
+        let build = |rows: &[&str]| HashLife::new(drawn(rows), Rule::CONWAY, topology, 0).unwrap();
+
+        let mut blinker = build(&["...", "OOO", "..."]);
+        let digest = blinker.digest();
+        blinker.evolve();
+        assert_ne!(blinker.digest(), digest);
+        blinker.evolve();
+        assert!(blinker.level() > HashLife::LEVEL_MIN);
+        assert_eq!(blinker.digest(), digest);
+        assert_eq!(blinker.clone().digest(), digest);
+        blinker.advance(1 << 20);
+        assert_eq!(blinker.digest(), digest);
+
+        let mut glider = build(&[".O.", "..O", "OOO"]);
+        let digest = glider.digest();
+        glider.advance(4);
+        assert_eq!(glider.population(), 5);
+        assert_ne!(glider.digest(), digest);
+        assert_eq!(
+            glider.digest(),
+            build(&["....", "..O.", "...O", ".OOO"]).digest()
+        );
+    }
+
+    /// Cells far apart must be listed without copying the space between
+    /// them, which is far too large.
+    #[test]
+    fn lists_cells_far_apart() {
+        let genesis = Matrix::from_value(Shape::new(1, 1), Cell::Dead).unwrap();
+        let mut hashlife = HashLife::new(genesis, Rule::CONWAY, Topology::Infinite, 0).unwrap();
+        let far = 1 << 60;
+        hashlife.set(far, -far, Cell::Alive);
+        hashlife.set(-far, far, Cell::Alive);
+        hashlife.set(0, 0, Cell::Alive);
+
+        assert_eq!(hashlife.alive(), [(-far, far), (0, 0), (far, -far)]);
+        assert!(hashlife.snapshot().is_err());
+    }
+}
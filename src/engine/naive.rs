@@ -0,0 +1,207 @@
+use super::{Engine, NEIGHBORS, in_bands, locate};
+use crate::biosquare::{Cell, Changes, Rect};
+use crate::rule::Rule;
+use crate::topology::{Edges, Topology};
+use anyhow::{Context, Result};
+use matreex::{Index, Matrix, Shape};
+use std::hash::{DefaultHasher, Hasher};
+use std::num::NonZeroUsize;
+
+#[derive(Debug, Clone)]
+pub struct Naive {
+    rule: Rule,
+    topology: Topology,
+    edges: Edges,
+    threads: NonZeroUsize,
+    population: usize,
+    changes: Changes,
+    total_changes: Changes,
+    flips: Vec<(isize, isize)>,
+    current: Matrix<Cell>,
+    next: Vec<Cell>,
+}
+
+impl Naive {
+    pub fn new(
+        genesis: Matrix<Cell>,
+        rule: Rule,
+        topology: Topology,
+        threads: NonZeroUsize,
+    ) -> Result<Self> {
+        let edges = topology.edges().with_context(|| {
+            format!("the naive engine does not support the {topology} topology")
+        })?;
+        let population = genesis
+            .iter_elements()
+            .filter(|cell| cell.is_alive())
+            .count();
+        let next = genesis.iter_elements().copied().collect();
+        let current = genesis;
+        let changes = Changes::default();
+        let total_changes = Changes::default();
+        let flips = Vec::new();
+
+        Ok(Self {
+            rule,
+            topology,
+            edges,
+            threads,
+            population,
+            changes,
+            total_changes,
+            flips,
+            current,
+            next,
+        })
+    }
+}
+
+impl Engine for Naive {
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    fn shape(&self) -> Shape {
+        self.current.shape()
+    }
+
+    fn population(&self) -> usize {
+        self.population
+    }
+
+    fn changes(&self) -> Option<Changes> {
+        Some(self.changes)
+    }
+
+    fn total_changes(&self) -> Option<Changes> {
+        Some(self.total_changes)
+    }
+
+    fn set_changes(&mut self, changes: Changes, total_changes: Changes) {
+        self.changes = changes;
+        self.total_changes = total_changes;
+    }
+
+    fn flips(&self) -> Option<&[(isize, isize)]> {
+        Some(&self.flips)
+    }
+
+    fn get(&self, row: isize, col: isize) -> Cell {
+        match locate(self.shape(), row, col) {
+            Some(index) => self.current[index],
+            None => Cell::Dead,
+        }
+    }
+
+    fn set(&mut self, row: isize, col: isize, cell: Cell) {
+        let Some(index) = locate(self.shape(), row, col) else {
+            return;
+        };
+        let current = &mut self.current[index];
+        match (*current, cell) {
+            (Cell::Dead, Cell::Alive) => self.population += 1,
+            (Cell::Alive, Cell::Dead) => self.population -= 1,
+            _ => (),
+        }
+        *current = cell;
+    }
+
+    fn evolve(&mut self) {
+        let shape = self.current.shape();
+        let world = Rect::new(0, 0, shape);
+        let current = &self.current;
+        let rule = self.rule;
+        let edges = self.edges;
+
+        let bands = in_bands(&mut self.next, shape, self.threads, |first_row, band| {
+            let mut births = 0;
+            let mut deaths = 0;
+            let mut flips = Vec::new();
+
+            for (offset, cell) in band.iter_mut().enumerate() {
+                let index = Index::new(first_row + offset / shape.ncols, offset % shape.ncols);
+                let (row, col) = world.location(index);
+                let neighbors = NEIGHBORS
+                    .into_iter()
+                    .filter_map(|offset| edges.resolve(row + offset.0, col + offset.1, shape))
+                    .filter(|&index| current[index].is_alive())
+                    .count();
+
+                *cell = current[index];
+                match (*cell, rule.apply(*cell, neighbors)) {
+                    (Cell::Dead, Cell::Alive) => {
+                        cell.revive();
+                        births += 1;
+                    }
+                    (Cell::Alive, Cell::Dead) => {
+                        cell.die();
+                        deaths += 1;
+                    }
+                    _ => continue,
+                }
+                flips.push((row, col));
+            }
+
+            (births, deaths, flips)
+        });
+
+        self.changes = Changes::default();
+        self.flips.clear();
+        for (births, deaths, flips) in bands {
+            self.population = self.population + births - deaths;
+            self.changes += Changes { births, deaths };
+            self.flips.extend(flips);
+        }
+        self.total_changes += self.changes;
+        for (cell, &next) in self.current.iter_elements_mut().zip(&self.next) {
+            *cell = next;
+        }
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        let world = Rect::new(0, 0, self.shape());
+        Rect::enclosing(
+            self.current
+                .iter_elements_with_index()
+                .filter(|(_, cell)| cell.is_alive())
+                .map(|(index, _)| world.location(index)),
+        )
+    }
+
+    fn digest(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        // Cells are packed into words, which is much faster to hash than one
+        // cell at a time.
+        let mut word = 0_u64;
+        for (index, cell) in self.current.iter_elements().enumerate() {
+            word = word << 1 | u64::from(cell.is_alive());
+            if index % 64 == 63 {
+                hasher.write_u64(word);
+                word = 0;
+            }
+        }
+        hasher.write_u64(word);
+        hasher.finish()
+    }
+
+    fn alive(&self) -> Vec<(isize, isize)> {
+        let world = Rect::new(0, 0, self.shape());
+        self.current
+            .iter_elements_with_index()
+            .filter(|(_, cell)| cell.is_alive())
+            .map(|(index, _)| world.location(index))
+            .collect()
+    }
+
+    fn snapshot(&self) -> Result<Matrix<Cell>> {
+        Ok(self.current.clone())
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Engine> {
+        Box::new(self.clone())
+    }
+}
@@ -0,0 +1,390 @@
+use super::{Engine, in_bands, locate};
+use crate::biosquare::{Cell, Changes, Rect};
+use crate::rule::Rule;
+use crate::topology::{Edge, Edges, Topology};
+use anyhow::{Context, Result};
+use matreex::{Index, Matrix, Shape};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::num::NonZeroUsize;
+
+const BITS: usize = u64::BITS as usize;
+
+/// Engine that packs each row into machine words, one bit per cell, and
+/// evolves 64 cells at a time with bitwise adder logic.
+#[derive(Debug, Clone)]
+pub struct Packed {
+    rule: Rule,
+    topology: Topology,
+    edges: Edges,
+    threads: NonZeroUsize,
+    shape: Shape,
+    words_per_row: usize,
+    population: usize,
+    changes: Changes,
+    total_changes: Changes,
+    flips: Vec<(isize, isize)>,
+    current: Vec<u64>,
+    next: Vec<u64>,
+}
+
+impl Packed {
+    pub fn new(
+        genesis: Matrix<Cell>,
+        rule: Rule,
+        topology: Topology,
+        threads: NonZeroUsize,
+    ) -> Result<Self> {
+        let edges = topology.edges().with_context(|| {
+            format!("the packed engine does not support the {topology} topology")
+        })?;
+        let shape = genesis.shape();
+        let words_per_row = shape.ncols.div_ceil(BITS);
+
+        let mut current = vec![0; shape.nrows * words_per_row];
+        let mut population = 0;
+        for (index, cell) in genesis.iter_elements_with_index() {
+            if cell.is_alive() {
+                current[index.row * words_per_row + index.col / BITS] |= 1 << (index.col % BITS);
+                population += 1;
+            }
+        }
+        let next = current.clone();
+        let changes = Changes::default();
+        let total_changes = Changes::default();
+        let flips = Vec::new();
+
+        Ok(Self {
+            rule,
+            topology,
+            edges,
+            threads,
+            shape,
+            words_per_row,
+            population,
+            changes,
+            total_changes,
+            flips,
+            current,
+            next,
+        })
+    }
+
+    /// Returns the word holding the cell at the given location, along with
+    /// the mask of its bit, or [`None`] if it lies beyond the world.
+    fn locate(&self, row: isize, col: isize) -> Option<(usize, u64)> {
+        let index = locate(self.shape, row, col)?;
+        let word = index.row * self.words_per_row + index.col / BITS;
+        let mask = 1 << (index.col % BITS);
+        Some((word, mask))
+    }
+}
+
+impl Engine for Packed {
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn topology(&self) -> Topology {
+        self.topology
+    }
+
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    fn population(&self) -> usize {
+        self.population
+    }
+
+    fn changes(&self) -> Option<Changes> {
+        Some(self.changes)
+    }
+
+    fn total_changes(&self) -> Option<Changes> {
+        Some(self.total_changes)
+    }
+
+    fn set_changes(&mut self, changes: Changes, total_changes: Changes) {
+        self.changes = changes;
+        self.total_changes = total_changes;
+    }
+
+    fn flips(&self) -> Option<&[(isize, isize)]> {
+        Some(&self.flips)
+    }
+
+    fn get(&self, row: isize, col: isize) -> Cell {
+        match self.locate(row, col) {
+            Some((word, mask)) => Cell::from(self.current[word] & mask != 0),
+            None => Cell::Dead,
+        }
+    }
+
+    fn set(&mut self, row: isize, col: isize, cell: Cell) {
+        let Some((word, mask)) = self.locate(row, col) else {
+            return;
+        };
+        let word = &mut self.current[word];
+        match (*word & mask != 0, cell) {
+            (false, Cell::Alive) => {
+                *word |= mask;
+                self.population += 1;
+            }
+            (true, Cell::Dead) => {
+                *word &= !mask;
+                self.population -= 1;
+            }
+            _ => (),
+        }
+    }
+
+    fn evolve(&mut self) {
+        let Shape { nrows, ncols } = self.shape;
+        if nrows == 0 || ncols == 0 {
+            return;
+        }
+
+        let Edges {
+            rows: row_edge,
+            cols: col_edge,
+        } = self.edges;
+        let rule = Rules::new(self.rule);
+        let width = self.words_per_row;
+        let current = &self.current;
+        let row = |row: usize| &current[row * width..(row + 1) * width];
+
+        // Crossing a twisted edge can only lead to the first or the last row,
+        // reversed.
+        let twisted = match row_edge {
+            Edge::Twist => [reverse(row(0), ncols), reverse(row(nrows - 1), ncols)],
+            _ => Default::default(),
+        };
+        let world = Rect::new(0, 0, self.shape);
+        let (height, _) = world.extents();
+        let neighbor = |index: usize, offset: isize| -> Option<&[u64]> {
+            let (index, _) = world.location(Index::new(index, 0));
+            let (index, reversed) = row_edge.resolve(index + offset, height)?;
+            // Resolved rows lie within the world, so the following cast is
+            // always safe.
+            match (reversed, index) {
+                (false, index) => Some(row(index as usize)),
+                (true, 0) => Some(&twisted[0]),
+                (true, _) => Some(&twisted[1]),
+            }
+        };
+
+        let summaries = in_bands(
+            &mut self.next,
+            self.shape,
+            self.threads,
+            |first_row, band| {
+                let mut population = 0;
+                let mut changes = Changes::default();
+                let mut flips = Vec::new();
+
+                for (offset, next) in band.chunks_exact_mut(width).enumerate() {
+                    let index = first_row + offset;
+                    let center = row(index);
+                    let above = neighbor(index, -1);
+                    let below = neighbor(index, 1);
+
+                    for (col, next) in next.iter_mut().enumerate() {
+                        let mut count = Count::default();
+                        for source in [above, below].into_iter().flatten() {
+                            count.add(west(source, col, ncols, col_edge));
+                            count.add(source[col]);
+                            count.add(east(source, col, ncols, col_edge));
+                        }
+                        count.add(west(center, col, ncols, col_edge));
+                        count.add(east(center, col, ncols, col_edge));
+
+                        *next = rule.apply(center[col], &count) & mask(col, ncols);
+                        population += next.count_ones() as usize;
+                        changes.births += (*next & !center[col]).count_ones() as usize;
+                        changes.deaths += (center[col] & !*next).count_ones() as usize;
+
+                        let mut flipped = *next ^ center[col];
+                        while flipped != 0 {
+                            let bit = flipped.trailing_zeros() as usize;
+                            flips.push(world.location(Index::new(index, col * BITS + bit)));
+                            flipped &= flipped - 1;
+                        }
+                    }
+                }
+
+                (population, changes, flips)
+            },
+        );
+
+        std::mem::swap(&mut self.current, &mut self.next);
+        self.population = 0;
+        self.changes = Changes::default();
+        self.flips.clear();
+        for (population, changes, flips) in summaries {
+            self.population += population;
+            self.changes += changes;
+            self.flips.extend(flips);
+        }
+        self.total_changes += self.changes;
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        if self.population == 0 {
+            return None;
+        }
+
+        // Only the first and the last alive cells of each word matter.
+        let world = Rect::new(0, 0, self.shape);
+        let width = self.words_per_row;
+        let ends = self
+            .current
+            .chunks_exact(width)
+            .enumerate()
+            .flat_map(|(row, words)| {
+                words
+                    .iter()
+                    .enumerate()
+                    .filter(|&(_, &word)| word != 0)
+                    .flat_map(move |(index, &word)| {
+                        let first = index * BITS + word.trailing_zeros() as usize;
+                        let last = index * BITS + (BITS - 1 - word.leading_zeros() as usize);
+                        [
+                            world.location(Index::new(row, first)),
+                            world.location(Index::new(row, last)),
+                        ]
+                    })
+            });
+        Rect::enclosing(ends)
+    }
+
+    fn alive(&self) -> Vec<(isize, isize)> {
+        let world = Rect::new(0, 0, self.shape);
+        let mut alive = Vec::with_capacity(self.population);
+        for (row, words) in self.current.chunks_exact(self.words_per_row).enumerate() {
+            for (index, &word) in words.iter().enumerate() {
+                let mut word = word;
+                while word != 0 {
+                    let col = index * BITS + word.trailing_zeros() as usize;
+                    alive.push(world.location(Index::new(row, col)));
+                    word &= word - 1;
+                }
+            }
+        }
+        alive
+    }
+
+    fn digest(&self) -> u64 {
+        let mut hasher = DefaultHasher::new();
+        self.current.hash(&mut hasher);
+        hasher.finish()
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Engine> {
+        Box::new(self.clone())
+    }
+}
+
+/// Bit-sliced neighbor counts of 64 cells, one bit plane per binary digit.
+#[derive(Debug, Default)]
+struct Count([u64; 4]);
+
+impl Count {
+    fn add(&mut self, mut carry: u64) {
+        for plane in &mut self.0 {
+            if carry == 0 {
+                break;
+            }
+            let next = *plane & carry;
+            *plane ^= carry;
+            carry = next;
+        }
+    }
+
+    /// Returns the cells whose neighbor count is `n`.
+    fn equals(&self, n: usize) -> u64 {
+        self.0.iter().enumerate().fold(!0, |acc, (digit, &plane)| {
+            if n >> digit & 1 == 1 {
+                acc & plane
+            } else {
+                acc & !plane
+            }
+        })
+    }
+}
+
+/// Neighbor counts that lead to birth and survival, respectively.
+#[derive(Debug)]
+struct Rules {
+    birth: Vec<usize>,
+    survival: Vec<usize>,
+}
+
+impl Rules {
+    fn new(rule: Rule) -> Self {
+        let birth = (0..=8).filter(|&n| rule.birth(n)).collect();
+        let survival = (0..=8).filter(|&n| rule.survival(n)).collect();
+        Self { birth, survival }
+    }
+
+    fn apply(&self, alive: u64, count: &Count) -> u64 {
+        let birth = self.birth.iter().fold(0, |acc, &n| acc | count.equals(n));
+        let survival = self
+            .survival
+            .iter()
+            .fold(0, |acc, &n| acc | count.equals(n));
+        !alive & birth | alive & survival
+    }
+}
+
+/// Returns the `word`-th word of `row`, shifted so that each cell holds its
+/// west neighbor.
+fn west(row: &[u64], word: usize, ncols: usize, edge: Edge) -> u64 {
+    let carry = match word {
+        0 => match edge {
+            Edge::Dead => 0,
+            Edge::Wrap | Edge::Twist => bit(row, ncols - 1),
+            Edge::Reflect => bit(row, 0),
+        },
+        word => row[word - 1] >> (BITS - 1),
+    };
+    row[word] << 1 | carry
+}
+
+/// Returns the `word`-th word of `row`, shifted so that each cell holds its
+/// east neighbor.
+fn east(row: &[u64], word: usize, ncols: usize, edge: Edge) -> u64 {
+    let mut shifted = row[word] >> 1;
+    if word + 1 < row.len() {
+        shifted |= row[word + 1] << (BITS - 1);
+    } else {
+        let carry = match edge {
+            Edge::Dead => 0,
+            Edge::Wrap | Edge::Twist => bit(row, 0),
+            Edge::Reflect => bit(row, ncols - 1),
+        };
+        shifted |= carry << ((ncols - 1) % BITS);
+    }
+    shifted
+}
+
+fn bit(row: &[u64], col: usize) -> u64 {
+    row[col / BITS] >> (col % BITS) & 1
+}
+
+/// Returns the bits of the `word`-th word that lie within the row.
+fn mask(word: usize, ncols: usize) -> u64 {
+    let remaining = ncols - word * BITS;
+    if remaining >= BITS {
+        !0
+    } else {
+        (1 << remaining) - 1
+    }
+}
+
+fn reverse(row: &[u64], ncols: usize) -> Vec<u64> {
+    let mut reversed = vec![0; row.len()];
+    for col in 0..ncols {
+        reversed[col / BITS] |= bit(row, ncols - 1 - col) << (col % BITS);
+    }
+    reversed
+}
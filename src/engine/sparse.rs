@@ -0,0 +1,166 @@
+use super::{Engine, NEIGHBORS};
+use crate::biosquare::{Cell, Changes, Rect};
+use crate::rule::Rule;
+use crate::topology::Topology;
+use anyhow::{Result, ensure};
+use matreex::{Matrix, Shape};
+use std::collections::{HashMap, HashSet};
+use std::hash::{DefaultHasher, Hash, Hasher};
+
+/// Engine that keeps track of alive cells only, on an infinite plane.
+///
+/// Both memory and time grow with the population rather than the area, so
+/// the world can grow without bounds, as with spaceships and puffers.
+#[derive(Debug, Clone)]
+pub struct Sparse {
+    rule: Rule,
+    shape: Shape,
+    alive: HashSet<(isize, isize)>,
+    changes: Changes,
+    total_changes: Changes,
+    flips: Vec<(isize, isize)>,
+}
+
+impl Sparse {
+    pub fn new(genesis: Matrix<Cell>, rule: Rule, topology: Topology) -> Result<Self> {
+        ensure!(
+            topology == Topology::Infinite,
+            "the sparse engine does not support the {topology} topology"
+        );
+        // Births from no neighbors would fill the infinite plane at once.
+        ensure!(
+            !rule.birth(0),
+            "the sparse engine does not support rules with birth on 0 neighbors"
+        );
+
+        let shape = genesis.shape();
+        let world = Rect::new(0, 0, shape);
+        let alive = genesis
+            .iter_elements_with_index()
+            .filter(|(_, cell)| cell.is_alive())
+            .map(|(index, _)| world.location(index))
+            .collect();
+
+        let changes = Changes::default();
+        let total_changes = Changes::default();
+        let flips = Vec::new();
+
+        Ok(Self {
+            rule,
+            shape,
+            alive,
+            changes,
+            total_changes,
+            flips,
+        })
+    }
+}
+
+impl Engine for Sparse {
+    fn rule(&self) -> Rule {
+        self.rule
+    }
+
+    fn topology(&self) -> Topology {
+        Topology::Infinite
+    }
+
+    fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    fn population(&self) -> usize {
+        self.alive.len()
+    }
+
+    fn changes(&self) -> Option<Changes> {
+        Some(self.changes)
+    }
+
+    fn total_changes(&self) -> Option<Changes> {
+        Some(self.total_changes)
+    }
+
+    fn set_changes(&mut self, changes: Changes, total_changes: Changes) {
+        self.changes = changes;
+        self.total_changes = total_changes;
+    }
+
+    fn flips(&self) -> Option<&[(isize, isize)]> {
+        Some(&self.flips)
+    }
+
+    fn get(&self, row: isize, col: isize) -> Cell {
+        Cell::from(self.alive.contains(&(row, col)))
+    }
+
+    fn set(&mut self, row: isize, col: isize, cell: Cell) {
+        match cell {
+            Cell::Dead => self.alive.remove(&(row, col)),
+            Cell::Alive => self.alive.insert((row, col)),
+        };
+    }
+
+    fn bounding_box(&self) -> Option<Rect> {
+        Rect::enclosing(self.alive.iter().copied())
+    }
+
+    fn alive(&self) -> Vec<(isize, isize)> {
+        let mut alive: Vec<_> = self.alive.iter().copied().collect();
+        alive.sort_unstable();
+        alive
+    }
+
+    fn digest(&self) -> u64 {
+        // The set is in no particular order, so the hashes of the cells are
+        // combined in a way that does not depend on it.
+        self.alive
+            .iter()
+            .map(|location| {
+                let mut hasher = DefaultHasher::new();
+                location.hash(&mut hasher);
+                hasher.finish()
+            })
+            .fold(0, u64::wrapping_add)
+    }
+
+    fn evolve(&mut self) {
+        // Only alive cells and their neighbors can be alive in the next
+        // generation.
+        let mut neighbors = HashMap::<_, usize>::with_capacity(self.alive.len() * NEIGHBORS.len());
+        for &(row, col) in &self.alive {
+            for (dr, dc) in NEIGHBORS {
+                *neighbors.entry((row + dr, col + dc)).or_default() += 1;
+            }
+        }
+
+        let mut next: HashSet<_> = neighbors
+            .iter()
+            .filter(|&(location, &count)| {
+                let cell = Cell::from(self.alive.contains(location));
+                self.rule.apply(cell, count).is_alive()
+            })
+            .map(|(&location, _)| location)
+            .collect();
+        if self.rule.survival(0) {
+            next.extend(
+                self.alive
+                    .iter()
+                    .filter(|location| !neighbors.contains_key(location)),
+            );
+        }
+
+        self.flips.clear();
+        self.flips.extend(next.difference(&self.alive));
+        let births = self.flips.len();
+        self.flips.extend(self.alive.difference(&next));
+        let deaths = self.flips.len() - births;
+        self.changes = Changes { births, deaths };
+        self.total_changes += self.changes;
+        self.alive = next;
+    }
+
+    fn boxed_clone(&self) -> Box<dyn Engine> {
+        Box::new(self.clone())
+    }
+}
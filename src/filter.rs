@@ -1,28 +1,86 @@
 pub use bit::Bit;
 pub use block::Block;
+pub use braille::Braille;
 pub use dye::Dye;
 pub use emoji::Emoji;
+pub use half::Half;
 pub use hanzi::Hanzi;
 
 use crate::biosquare::Cell;
+use crossterm::style::StyledContent;
+use matreex::Shape;
 
 mod bit;
 mod block;
+mod braille;
 mod dye;
 mod emoji;
+mod half;
 mod hanzi;
 
-/// Visual filter for [`Cell`].
+/// Visual filter for patches of [`Cell`]s, each observed as a single glyph.
 pub trait Filter {
-    /// Returns the filtered representation of the given cell.
+    /// Returns the filtered representation of the given patch.
     ///
     /// The returned values should be visually distinct from each other and of
-    /// the same length.
-    fn filter(&self, cell: Cell) -> &str;
+    /// the same width. Their styles are kept apart from their content, so that
+    /// consecutive glyphs of the same style need not repeat it.
+    fn filter(&self, patch: Patch) -> StyledContent<&str>;
+
+    /// Returns the shape of the patches, which must not cover more than
+    /// [`Patch::CELLS_MAX`] cells.
+    fn patch(&self) -> Shape {
+        Shape::new(1, 1)
+    }
+
+    /// Returns the number of terminal columns each glyph occupies.
+    fn width(&self) -> u16 {
+        2
+    }
 }
 
 impl Filter for Box<dyn Filter> {
-    fn filter(&self, cell: Cell) -> &str {
-        self.as_ref().filter(cell)
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        self.as_ref().filter(patch)
+    }
+
+    fn patch(&self) -> Shape {
+        self.as_ref().patch()
+    }
+
+    fn width(&self) -> u16 {
+        self.as_ref().width()
+    }
+}
+
+/// Cells observed as a single glyph, one bit per cell in row-major order.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Patch(u8);
+
+impl Patch {
+    pub const CELLS_MAX: usize = u8::BITS as usize;
+
+    /// Returns the patch with the given cells, in row-major order.
+    pub fn new<I>(cells: I) -> Self
+    where
+        I: IntoIterator<Item = Cell>,
+    {
+        let bits = cells
+            .into_iter()
+            .take(Self::CELLS_MAX)
+            .enumerate()
+            .fold(0, |bits, (index, cell)| {
+                bits | u8::from(cell.is_alive()) << index
+            });
+        Self(bits)
+    }
+
+    pub fn bits(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the `index`-th cell in row-major order.
+    pub fn cell(self, index: usize) -> Cell {
+        Cell::from(self.0 >> index & 1 == 1)
     }
 }
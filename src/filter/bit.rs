@@ -1,19 +1,19 @@
-use super::Filter;
+use super::{Filter, Patch};
 use crate::biosquare::Cell;
-use crossterm::style::Stylize;
+use crossterm::style::{StyledContent, Stylize};
 use std::sync::LazyLock;
 
-static DEAD: LazyLock<String> = LazyLock::new(|| "0 ".green().bold().dim().to_string());
-static ALIVE: LazyLock<String> = LazyLock::new(|| "1 ".green().bold().to_string());
+static DEAD: LazyLock<StyledContent<&str>> = LazyLock::new(|| "0 ".green().bold().dim());
+static ALIVE: LazyLock<StyledContent<&str>> = LazyLock::new(|| "1 ".green().bold());
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Bit;
 
 impl Filter for Bit {
-    fn filter(&self, cell: Cell) -> &str {
-        match cell {
-            Cell::Dead => &DEAD,
-            Cell::Alive => &ALIVE,
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        match patch.cell(0) {
+            Cell::Dead => *DEAD,
+            Cell::Alive => *ALIVE,
         }
     }
 }
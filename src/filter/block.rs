@@ -1,14 +1,15 @@
-use super::Filter;
+use super::{Filter, Patch};
 use crate::biosquare::Cell;
+use crossterm::style::{StyledContent, Stylize};
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Block;
 
 impl Filter for Block {
-    fn filter(&self, cell: Cell) -> &str {
-        match cell {
-            Cell::Dead => "  ",
-            Cell::Alive => "██",
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        match patch.cell(0) {
+            Cell::Dead => "  ".stylize(),
+            Cell::Alive => "██".stylize(),
         }
     }
 }
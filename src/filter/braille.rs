@@ -0,0 +1,42 @@
+use super::{Filter, Patch};
+use crossterm::style::{StyledContent, Stylize};
+use matreex::Shape;
+use std::sync::LazyLock;
+
+/// Dots of a Braille pattern for each cell of a `4×2` patch, in row-major
+/// order.
+const DOTS: [u32; 8] = [0x01, 0x08, 0x02, 0x10, 0x04, 0x20, 0x40, 0x80];
+
+static GLYPHS: LazyLock<Vec<String>> = LazyLock::new(|| {
+    (0..=u8::MAX)
+        .map(|bits| {
+            let dots = DOTS
+                .iter()
+                .enumerate()
+                .filter(|&(index, _)| bits >> index & 1 == 1)
+                .fold(0, |dots, (_, &dot)| dots | dot);
+            char::from_u32(0x2800 + dots)
+                .unwrap_or_else(|| unreachable!())
+                .to_string()
+        })
+        .collect()
+});
+
+/// Eight cells per column, four rows by two columns, drawn with Braille
+/// patterns.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Braille;
+
+impl Filter for Braille {
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        GLYPHS[usize::from(patch.bits())].as_str().stylize()
+    }
+
+    fn patch(&self) -> Shape {
+        Shape::new(4, 2)
+    }
+
+    fn width(&self) -> u16 {
+        1
+    }
+}
@@ -1,28 +1,28 @@
-use super::Filter;
+use super::{Filter, Patch};
 use crate::biosquare::Cell;
-use crossterm::style::{Color, Stylize};
+use crossterm::style::{Color, StyledContent, Stylize};
 
 const PLACEHOLDER: &str = "  ";
 
 #[derive(Debug, PartialEq, Eq)]
 pub struct Dye {
-    dead: String,
-    alive: String,
+    dead: StyledContent<&'static str>,
+    alive: StyledContent<&'static str>,
 }
 
 impl Dye {
     pub fn new(dead: Color, alive: Color) -> Self {
-        let dead = PLACEHOLDER.on(dead).to_string();
-        let alive = PLACEHOLDER.on(alive).to_string();
+        let dead = PLACEHOLDER.on(dead);
+        let alive = PLACEHOLDER.on(alive);
         Self { dead, alive }
     }
 }
 
 impl Filter for Dye {
-    fn filter(&self, cell: Cell) -> &str {
-        match cell {
-            Cell::Dead => &self.dead,
-            Cell::Alive => &self.alive,
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        match patch.cell(0) {
+            Cell::Dead => self.dead,
+            Cell::Alive => self.alive,
         }
     }
 }
@@ -1,5 +1,6 @@
-use super::Filter;
+use super::{Filter, Patch};
 use crate::biosquare::Cell;
+use crossterm::style::{StyledContent, Stylize};
 use rand::RngExt;
 
 const DEAD_SYMBOLS: &str = "🤢🥶🥵😡🤬😈👿🤡👻";
@@ -33,10 +34,10 @@ impl Emoji {
 }
 
 impl Filter for Emoji {
-    fn filter(&self, cell: Cell) -> &str {
-        match cell {
-            Cell::Dead => self.dead,
-            Cell::Alive => self.alive,
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        match patch.cell(0) {
+            Cell::Dead => self.dead.stylize(),
+            Cell::Alive => self.alive.stylize(),
         }
     }
 }
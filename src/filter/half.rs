@@ -0,0 +1,41 @@
+use super::{Filter, Patch};
+use crossterm::style::{Color, StyledContent, Stylize};
+use matreex::Shape;
+
+const GLYPHS: [&str; 4] = [" ", "▀", "▄", "█"];
+
+/// Two vertically stacked cells per column, drawn with half blocks.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Half {
+    glyphs: [StyledContent<&'static str>; 4],
+}
+
+impl Half {
+    /// Draws alive cells as blocks in the foreground color of the terminal.
+    pub fn plain() -> Self {
+        let glyphs = GLYPHS.map(Stylize::stylize);
+        Self { glyphs }
+    }
+
+    /// Paints the upper and the lower cell of each column separately, in the
+    /// foreground and the background color of an upper half block.
+    pub fn dyed(dead: Color, alive: Color) -> Self {
+        let color = |bit| if bit == 1 { alive } else { dead };
+        let glyphs = [0, 1, 2, 3].map(|bits| GLYPHS[1].with(color(bits & 1)).on(color(bits >> 1)));
+        Self { glyphs }
+    }
+}
+
+impl Filter for Half {
+    fn filter(&self, patch: Patch) -> StyledContent<&str> {
+        self.glyphs[usize::from(patch.bits() & 0b11)]
+    }
+
+    fn patch(&self) -> Shape {
+        Shape::new(2, 1)
+    }
+
+    fn width(&self) -> u16 {
+        1
+    }
+}
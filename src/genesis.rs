@@ -1,10 +1,14 @@
 use crate::biosquare::Cell;
-use anyhow::Result;
+use crate::pattern::{self, Pattern};
+use anyhow::{Result, ensure};
 use matreex::{Matrix, Shape};
 use rand::RngExt;
 use rand::rngs::ChaCha8Rng;
 use rand_seeder::Seeder;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
 use std::hash::Hash;
+use std::str::FromStr;
 
 #[derive(Debug)]
 pub struct Genesis {
@@ -26,6 +30,34 @@ impl Genesis {
         };
         Matrix::from_fn(self.shape, |_| Cell::from(rng.random_bool(density.0))).map_err(Into::into)
     }
+
+    /// Places `pattern` in an otherwise dead world, with its top-left corner
+    /// at `offset`, or centered if `offset` is [`None`].
+    pub fn pattern(&self, pattern: &Pattern, offset: Option<Offset>) -> Result<Matrix<Cell>> {
+        let world = self.shape;
+        let shape = pattern.shape();
+        pattern::ensure_fits(shape, world)?;
+
+        let offset = offset.unwrap_or(Offset {
+            row: (world.nrows - shape.nrows) / 2,
+            col: (world.ncols - shape.ncols) / 2,
+        });
+        ensure!(
+            offset.row <= world.nrows - shape.nrows && offset.col <= world.ncols - shape.ncols,
+            "pattern of {}×{} cells at offset {offset} exceeds a world of {}×{} cells",
+            shape.nrows,
+            shape.ncols,
+            world.nrows,
+            world.ncols,
+        );
+
+        let mut matrix = Matrix::from_value(world, Cell::Dead)?;
+        for &(row, col) in pattern.alive() {
+            matrix[(offset.row + row, offset.col + col)] = Cell::Alive;
+        }
+
+        Ok(matrix)
+    }
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -39,4 +71,43 @@ impl Density {
             None
         }
     }
+
+    pub fn value(self) -> f64 {
+        self.0
+    }
+}
+
+/// Location of the top-left corner of a pattern in the world.
+#[derive(Debug, Clone, Copy)]
+pub struct Offset {
+    row: usize,
+    col: usize,
 }
+
+impl FromStr for Offset {
+    type Err = ParseOffsetError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (row, col) = s.split_once(',').ok_or(ParseOffsetError)?;
+        let row = row.trim().parse().map_err(|_| ParseOffsetError)?;
+        let col = col.trim().parse().map_err(|_| ParseOffsetError)?;
+        Ok(Self { row, col })
+    }
+}
+
+impl Display for Offset {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{},{}", self.row, self.col)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ParseOffsetError;
+
+impl Display for ParseOffsetError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("expected `ROW,COL` with non-negative integers")
+    }
+}
+
+impl Error for ParseOffsetError {}
@@ -0,0 +1,104 @@
+use crate::biosquare::BioSquare;
+use crate::stability::{Cycle, Detector};
+use anyhow::Result;
+use std::io::Write;
+use std::path::PathBuf;
+use std::time::Instant;
+
+/// Simulation driver that evolves the world as fast as possible, without a
+/// terminal, and reports the final statistics.
+///
+/// The run stops early once the world repeats itself, and reports the period
+/// it has settled into.
+#[derive(Debug)]
+pub struct Headless<O>
+where
+    O: Write,
+{
+    biosquare: BioSquare,
+    generations: usize,
+    save_on_exit: Option<PathBuf>,
+    output: O,
+}
+
+impl<O> Headless<O>
+where
+    O: Write,
+{
+    pub fn new(
+        biosquare: BioSquare,
+        generations: usize,
+        save_on_exit: Option<PathBuf>,
+        output: O,
+    ) -> Self {
+        Self {
+            biosquare,
+            generations,
+            save_on_exit,
+            output,
+        }
+    }
+
+    pub fn run(&mut self) -> Result<()> {
+        // Generations jumped over beforehand are not timed, so they do not
+        // count towards the speed.
+        let first = self.biosquare.generation();
+        let start = Instant::now();
+        let cycle = self.advance_until_stable();
+        let elapsed = start.elapsed();
+
+        if let Some(path) = &self.save_on_exit {
+            self.biosquare.save(path)?;
+        }
+
+        let rule = self.biosquare.rule();
+        let generation = self.biosquare.generation();
+        let population = self.biosquare.population();
+        let bounding_box = self.biosquare.bounding_box().unwrap_or_default();
+        let density = self.biosquare.density();
+        let secs = elapsed.as_secs_f64();
+        let speed = (generation - first) as f64 / secs;
+
+        writeln!(self.output, "rule: {rule}")?;
+        writeln!(self.output, "generation: {generation}")?;
+        writeln!(self.output, "population: {population}")?;
+        writeln!(
+            self.output,
+            "bounding box: {} x {}",
+            bounding_box.nrows, bounding_box.ncols
+        )?;
+        writeln!(self.output, "density: {density:.6}")?;
+        if let Some(changes) = self.biosquare.changes() {
+            writeln!(self.output, "births: {}", changes.births)?;
+            writeln!(self.output, "deaths: {}", changes.deaths)?;
+            writeln!(self.output, "activity: {}", changes.activity())?;
+        }
+        if let Some(changes) = self.biosquare.total_changes() {
+            writeln!(self.output, "total births: {}", changes.births)?;
+            writeln!(self.output, "total deaths: {}", changes.deaths)?;
+        }
+        writeln!(self.output, "elapsed: {secs:.6} s")?;
+        writeln!(self.output, "generations/sec: {speed:.2}")?;
+        match cycle {
+            Some(cycle) => writeln!(self.output, "stability: {cycle}")?,
+            None => writeln!(self.output, "stability: evolving")?,
+        }
+        self.output.flush()?;
+
+        Ok(())
+    }
+
+    /// Evolves the world one generation at a time, and stops early once it
+    /// repeats itself, returning the cycle it has settled into.
+    fn advance_until_stable(&mut self) -> Option<Cycle> {
+        let mut detector = Detector::new();
+        detector.observe(&self.biosquare);
+        for _ in 0..self.generations {
+            self.biosquare.advance(1);
+            if let Some(cycle) = detector.observe(&self.biosquare) {
+                return Some(cycle);
+            }
+        }
+        None
+    }
+}
@@ -0,0 +1,400 @@
+use crate::biosquare::{BioSquare, Cell, Rect, Stamp};
+use crate::topology::Topology;
+use matreex::{Index, Matrix, Shape};
+use std::collections::VecDeque;
+use std::{iter, mem};
+
+/// Bounded record of the recent states of the world, to rewind through.
+///
+/// Only the cells flipped between consecutive states are kept, so that the
+/// cost grows with the activity rather than the size of the world. Flipping
+/// the same cells again goes the other way, so each record serves to step
+/// both backward and forward.
+#[derive(Debug)]
+pub struct History {
+    capacity: usize,
+    /// Shape of the world, or [`None`] if it is infinite.
+    world: Option<Shape>,
+    /// Changes between consecutive states, oldest first.
+    deltas: VecDeque<Delta>,
+    /// Memory taken by the cells flipped in `deltas`, in bytes.
+    bytes: usize,
+    /// Number of changes undone from the latest state.
+    rewound: usize,
+    /// Stamp of the current state as far as the history is concerned.
+    stamp: Stamp,
+    tracking: Tracking,
+}
+
+#[derive(Debug)]
+struct Delta {
+    before: Stamp,
+    after: Stamp,
+    flips: Flips,
+}
+
+/// Cells flipped by a change, in whichever form takes the least memory.
+#[derive(Debug)]
+enum Flips {
+    /// Locations of the cells, as needed in an infinite world.
+    Locations(Vec<(isize, isize)>),
+    /// Indices of the cells within a bounded world, row by row.
+    Indices(Vec<u32>),
+    /// One bit per cell of a bounded world, row by row, set if flipped.
+    Bitset(Vec<u64>),
+}
+
+/// Way the cells flipped between consecutive states are found.
+#[derive(Debug)]
+enum Tracking {
+    /// Taken from the world, whose engine tells which cells it flips.
+    Journal,
+    /// Compared against a copy of the current state, or [`None`] if too
+    /// large to be recorded.
+    Frames(Option<Frame>),
+}
+
+impl History {
+    /// Maximum area compared between two states, beyond which the history
+    /// is dropped rather than stalling the screen.
+    const AREA_MAX: usize = 1 << 26;
+
+    /// Maximum memory taken by the cells flipped, beyond which the oldest
+    /// changes are forgotten even if fewer than the capacity.
+    const BYTES_MAX: usize = 1 << 28;
+
+    /// Creates a history of up to `capacity` changes, starting from
+    /// `biosquare`, which keeps track of the cells flipped from then on if
+    /// it can.
+    pub fn new(capacity: usize, biosquare: &mut BioSquare) -> Self {
+        let stamp = biosquare.stamp();
+        let world = match biosquare.topology() {
+            Topology::Infinite => None,
+            _ => Some(biosquare.shape()),
+        };
+        let tracking = match capacity {
+            0 => Tracking::Frames(None),
+            _ if biosquare.keep_flips() => Tracking::Journal,
+            _ => Tracking::Frames(Frame::observe(biosquare)),
+        };
+        Self {
+            capacity,
+            world,
+            deltas: VecDeque::new(),
+            bytes: 0,
+            rewound: 0,
+            stamp,
+            tracking,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.deltas.len()
+    }
+
+    /// Returns the number of changes undone from the latest state.
+    pub fn rewound(&self) -> usize {
+        self.rewound
+    }
+
+    /// Records the change from the last state to `biosquare`, forgetting
+    /// the changes undone before, if any.
+    pub fn record(&mut self, biosquare: &mut BioSquare) {
+        if self.capacity == 0 {
+            return;
+        }
+        for delta in self.deltas.drain(self.deltas.len() - self.rewound..) {
+            self.bytes -= delta.flips.bytes();
+        }
+        self.rewound = 0;
+
+        let flips = match &mut self.tracking {
+            Tracking::Journal => biosquare.take_flips().unwrap_or_default(),
+            Tracking::Frames(last) => {
+                let next = Frame::observe(biosquare);
+                let flips = last
+                    .as_ref()
+                    .zip(next.as_ref())
+                    .map(|(last, next)| last.diff(next));
+                *last = next;
+                let Some(flips) = flips else {
+                    self.deltas.clear();
+                    self.bytes = 0;
+                    self.stamp = biosquare.stamp();
+                    return;
+                };
+                flips
+            }
+        };
+
+        let delta = Delta {
+            before: self.stamp,
+            after: biosquare.stamp(),
+            flips: Flips::new(flips, self.world),
+        };
+        self.stamp = delta.after;
+        self.bytes += delta.flips.bytes();
+        self.deltas.push_back(delta);
+        while self.deltas.len() > self.capacity || self.bytes > Self::BYTES_MAX {
+            let Some(oldest) = self.deltas.pop_front() else {
+                break;
+            };
+            self.bytes -= oldest.flips.bytes();
+        }
+    }
+
+    /// Undoes up to `changes` recorded changes on `biosquare`.
+    pub fn back(&mut self, biosquare: &mut BioSquare, changes: usize) {
+        for _ in 0..changes {
+            let Some(index) = self.deltas.len().checked_sub(self.rewound + 1) else {
+                break;
+            };
+            let delta = &self.deltas[index];
+            biosquare.restore(delta.flips.locations(self.world), delta.before);
+            self.stamp = delta.before;
+            self.rewound += 1;
+        }
+        self.sync(biosquare);
+    }
+
+    /// Redoes up to `changes` undone changes on `biosquare`.
+    pub fn forward(&mut self, biosquare: &mut BioSquare, changes: usize) {
+        for _ in 0..changes.min(self.rewound) {
+            self.rewound -= 1;
+            let delta = &self.deltas[self.deltas.len() - self.rewound - 1];
+            biosquare.restore(delta.flips.locations(self.world), delta.after);
+            self.stamp = delta.after;
+        }
+        self.sync(biosquare);
+    }
+
+    /// Takes `biosquare` as the current state after moving through history.
+    fn sync(&mut self, biosquare: &BioSquare) {
+        if let Tracking::Frames(last) = &mut self.tracking {
+            *last = Frame::observe(biosquare);
+        }
+    }
+}
+
+impl Flips {
+    /// Packs the locations of the cells flipped within a world of the given
+    /// shape, or [`None`] if it is infinite.
+    fn new(locations: Vec<(isize, isize)>, world: Option<Shape>) -> Self {
+        let Some(shape) = world else {
+            return Self::Locations(locations);
+        };
+        let area = shape.nrows * shape.ncols;
+        let words = area.div_ceil(u64::BITS as usize);
+        // Locations within a bounded world are not negative, so the
+        // following casts are always safe.
+        let indices = locations
+            .iter()
+            .map(|&(row, col)| row as usize * shape.ncols + col as usize);
+
+        let bitset_bytes = words * mem::size_of::<u64>();
+        if u32::try_from(area).is_ok() && locations.len() * mem::size_of::<u32>() <= bitset_bytes {
+            // Indices lie within the area, so the following cast is always
+            // safe.
+            Self::Indices(indices.map(|index| index as u32).collect())
+        } else if bitset_bytes < mem::size_of_val(locations.as_slice()) {
+            let mut bitset = vec![0; words];
+            for index in indices {
+                bitset[index / u64::BITS as usize] |= 1 << (index % u64::BITS as usize);
+            }
+            Self::Bitset(bitset)
+        } else {
+            Self::Locations(locations)
+        }
+    }
+
+    /// Returns the memory taken by the cells, in bytes.
+    fn bytes(&self) -> usize {
+        match self {
+            Self::Locations(locations) => mem::size_of_val(locations.as_slice()),
+            Self::Indices(indices) => mem::size_of_val(indices.as_slice()),
+            Self::Bitset(bitset) => mem::size_of_val(bitset.as_slice()),
+        }
+    }
+
+    /// Returns the locations of the cells within a world of the given shape,
+    /// or [`None`] if it is infinite.
+    fn locations(&self, world: Option<Shape>) -> Box<dyn Iterator<Item = (isize, isize)> + '_> {
+        let shape = world.unwrap_or_default();
+        let rect = Rect::new(0, 0, shape);
+        let location =
+            move |index: usize| rect.location(Index::new(index / shape.ncols, index % shape.ncols));
+        match self {
+            Self::Locations(locations) => Box::new(locations.iter().copied()),
+            Self::Indices(indices) => {
+                Box::new(indices.iter().map(move |&index| location(index as usize)))
+            }
+            Self::Bitset(bitset) => {
+                Box::new(bitset.iter().enumerate().flat_map(move |(word, &bits)| {
+                    // Each clears the lowest bit set of the previous.
+                    let nonzero = |bits: u64| (bits != 0).then_some(bits);
+                    iter::successors(nonzero(bits), move |&bits| nonzero(bits & (bits - 1))).map(
+                        move |bits| {
+                            location(word * u64::BITS as usize + bits.trailing_zeros() as usize)
+                        },
+                    )
+                }))
+            }
+        }
+    }
+}
+
+/// Copy of the cells of a state, from which dead cells far away may be left
+/// out.
+#[derive(Debug)]
+struct Frame {
+    top: isize,
+    left: isize,
+    cells: Matrix<Cell>,
+}
+
+impl Frame {
+    /// Copies the whole world, or its bounding box if it is infinite, or
+    /// returns [`None`] if that is too large to compare.
+    fn observe(biosquare: &BioSquare) -> Option<Self> {
+        let rect = match biosquare.topology() {
+            Topology::Infinite => biosquare.bounding_box().unwrap_or_default(),
+            _ => Rect::new(0, 0, biosquare.shape()),
+        };
+        if rect.nrows.saturating_mul(rect.ncols) > History::AREA_MAX {
+            return None;
+        }
+        let cells = biosquare.observe().ok()?;
+        Some(Self {
+            top: rect.top,
+            left: rect.left,
+            cells,
+        })
+    }
+
+    /// Returns the locations of the cells that differ from `other`.
+    fn diff(&self, other: &Self) -> Vec<(isize, isize)> {
+        // Cells can only differ where observed in either state.
+        let corners = [self, other]
+            .into_iter()
+            .filter_map(|frame| frame.rect())
+            .flat_map(|rect| {
+                let (height, width) = rect.extents();
+                [
+                    (rect.top, rect.left),
+                    (rect.top + height - 1, rect.left + width - 1),
+                ]
+            });
+        let Some(rect) = Rect::enclosing(corners) else {
+            return Vec::new();
+        };
+
+        let Rect { top, left, .. } = rect;
+        let (height, width) = rect.extents();
+        (top..top + height)
+            .flat_map(|row| (left..left + width).map(move |col| (row, col)))
+            .filter(|&(row, col)| self.get(row, col) != other.get(row, col))
+            .collect()
+    }
+
+    /// Returns the region covered by the frame, or [`None`] if empty.
+    fn rect(&self) -> Option<Rect> {
+        let shape = self.cells.shape();
+        (shape.nrows > 0 && shape.ncols > 0).then(|| Rect::new(self.top, self.left, shape))
+    }
+
+    /// Returns the cell at the given location, dead if left out.
+    fn get(&self, row: isize, col: isize) -> Cell {
+        let shape = self.cells.shape();
+        let row = row
+            .checked_sub(self.top)
+            .and_then(|row| usize::try_from(row).ok());
+        let col = col
+            .checked_sub(self.left)
+            .and_then(|col| usize::try_from(col).ok());
+        match (row, col) {
+            (Some(row), Some(col)) if row < shape.nrows && col < shape.ncols => {
+                self.cells[Index::new(row, col)]
+            }
+            _ => Cell::Dead,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::engine::EngineKind;
+    use crate::genesis::{Density, Genesis};
+    use std::num::NonZeroUsize;
+
+    fn round_trip(locations: &[(isize, isize)], world: Option<Shape>) -> Flips {
+        let flips = Flips::new(locations.to_vec(), world);
+        let mut unpacked: Vec<_> = flips.locations(world).collect();
+        unpacked.sort_unstable();
+        let mut expected = locations.to_vec();
+        expected.sort_unstable();
+        assert_eq!(unpacked, expected);
+        flips
+    }
+
+    #[test]
+    fn packs_flips_into_the_smallest_form() {
+        let world = Some(Shape::new(100, 130));
+        let few = [(99, 0), (0, 129), (50, 64), (3, 63)];
+        assert!(matches!(round_trip(&few, world), Flips::Indices(_)));
+
+        let many: Vec<_> = (0..50)
+            .flat_map(|row| (0..65).map(move |col| (row, 2 * col)))
+            .collect();
+        let flips = round_trip(&many, world);
+        assert!(matches!(flips, Flips::Bitset(_)));
+        assert_eq!(flips.bytes(), (100 * 130_usize).div_ceil(64) * 8);
+
+        let far = [(-1 << 40, 5), (1 << 40, -5)];
+        assert!(matches!(round_trip(&far, None), Flips::Locations(_)));
+
+        // Indices of a world too large to fit in 32 bits.
+        let huge = Some(Shape::new(1 << 20, 1 << 20));
+        let few = [(0, 0), ((1 << 20) - 1, (1 << 20) - 1)];
+        assert!(matches!(round_trip(&few, huge), Flips::Locations(_)));
+    }
+
+    /// Rewinding and replaying must go through the same states as evolving,
+    /// whether the flips are taken from the engine or found by comparison.
+    #[test]
+    fn rewinds_and_replays() {
+        let genesis = Genesis::new(Shape::new(64, 64))
+            .random(Density::new(0.4).unwrap(), Some(7))
+            .unwrap();
+        let threads = NonZeroUsize::new(1).unwrap();
+        for kind in [EngineKind::Packed, EngineKind::HashLife] {
+            let topology = kind.topology();
+            let rule = Default::default();
+            let engine = kind
+                .build(genesis.clone(), rule, topology, threads, 0)
+                .unwrap();
+            let mut biosquare = BioSquare::new(engine);
+            let mut history = History::new(8, &mut biosquare);
+
+            let mut states = vec![biosquare.observe().unwrap()];
+            for _ in 0..12 {
+                biosquare.advance(1);
+                history.record(&mut biosquare);
+                states.push(biosquare.observe().unwrap());
+            }
+            assert_eq!(history.len(), 8);
+
+            for generation in (4..12).rev() {
+                history.back(&mut biosquare, 1);
+                assert_eq!(biosquare.generation(), generation, "{kind:?}");
+                assert_eq!(biosquare.observe().unwrap(), states[generation], "{kind:?}");
+            }
+            history.back(&mut biosquare, 1);
+            assert_eq!(biosquare.generation(), 4, "{kind:?}");
+
+            history.forward(&mut biosquare, 8);
+            assert_eq!(biosquare.generation(), 12, "{kind:?}");
+            assert_eq!(biosquare.observe().unwrap(), states[12], "{kind:?}");
+        }
+    }
+}
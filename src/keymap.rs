@@ -0,0 +1,479 @@
+use anyhow::{Result, bail, ensure};
+use clap::ValueEnum;
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Named action that keys can be bound to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, ValueEnum)]
+pub enum Action {
+    SlowDown,
+    SpeedUp,
+    Pause,
+    /// Pause and step one generation, or as many as the count typed before.
+    Step,
+    /// Pause and rewind one frame, or as many as the count typed before.
+    Rewind,
+    /// Pause and replay one frame, or as many as the count typed before.
+    Replay,
+    Flip,
+    Reset,
+    Save,
+    PanLeft,
+    PanDown,
+    PanUp,
+    PanRight,
+    Center,
+    Edit,
+    /// Show or hide the key bindings.
+    Help,
+    Quit,
+    CursorLeft,
+    CursorDown,
+    CursorUp,
+    CursorRight,
+    Toggle,
+}
+
+impl Action {
+    /// Returns `true` if the action only applies in edit mode, where its keys
+    /// take precedence over the others.
+    pub fn is_editing(self) -> bool {
+        matches!(
+            self,
+            Self::CursorLeft | Self::CursorDown | Self::CursorUp | Self::CursorRight | Self::Toggle
+        )
+    }
+}
+
+impl Display for Action {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = self.to_possible_value().unwrap_or_else(|| unreachable!());
+        f.write_str(value.get_name())
+    }
+}
+
+/// Key along with the modifiers held down, such as `ctrl-c`.
+///
+/// The case of a character already tells whether shift is held down, so
+/// shift only shows as a modifier of other keys, as in `shift-left`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Key {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl Key {
+    const NAMES: [(&str, KeyCode); 14] = [
+        ("left", KeyCode::Left),
+        ("right", KeyCode::Right),
+        ("up", KeyCode::Up),
+        ("down", KeyCode::Down),
+        ("esc", KeyCode::Esc),
+        ("enter", KeyCode::Enter),
+        ("tab", KeyCode::Tab),
+        ("backspace", KeyCode::Backspace),
+        ("home", KeyCode::Home),
+        ("end", KeyCode::End),
+        ("pageup", KeyCode::PageUp),
+        ("pagedown", KeyCode::PageDown),
+        ("insert", KeyCode::Insert),
+        ("delete", KeyCode::Delete),
+    ];
+
+    const fn new(code: KeyCode, modifiers: KeyModifiers) -> Self {
+        Self { code, modifiers }
+    }
+
+    const fn char(key: char) -> Self {
+        Self::new(KeyCode::Char(key), KeyModifiers::NONE)
+    }
+
+    const fn code(code: KeyCode) -> Self {
+        Self::new(code, KeyModifiers::NONE)
+    }
+
+    /// Returns the value of a digit typed without modifiers, which makes up
+    /// the count of the next action.
+    pub fn digit(self) -> Option<usize> {
+        match self.code {
+            KeyCode::Char(key) if self.modifiers.is_empty() => {
+                key.to_digit(10).map(|digit| digit as usize)
+            }
+            _ => None,
+        }
+    }
+}
+
+impl From<KeyEvent> for Key {
+    fn from(event: KeyEvent) -> Self {
+        let mut modifiers = event.modifiers & !(KeyModifiers::SUPER | KeyModifiers::META);
+        if let KeyCode::Char(_) = event.code {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Self::new(event.code, modifiers)
+    }
+}
+
+impl FromStr for Key {
+    type Err = ParseBindingError;
+
+    /// Parses a character or a key name such as `left`, `esc` or `f5`,
+    /// prefixed by any of `ctrl-`, `alt-` and `shift-`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        const MODIFIERS: [(&str, KeyModifiers); 3] = [
+            ("ctrl-", KeyModifiers::CONTROL),
+            ("alt-", KeyModifiers::ALT),
+            ("shift-", KeyModifiers::SHIFT),
+        ];
+
+        let mut modifiers = KeyModifiers::NONE;
+        let mut rest = s;
+        // A lone `-` is a key of its own, not a separator.
+        while rest.len() > 1 {
+            let Some((prefix, modifier)) = MODIFIERS.into_iter().find(|(prefix, _)| {
+                rest.get(..prefix.len())
+                    .is_some_and(|head| head.eq_ignore_ascii_case(prefix))
+            }) else {
+                break;
+            };
+            rest = &rest[prefix.len()..];
+            modifiers |= modifier;
+        }
+
+        let mut chars = rest.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(key), None) => KeyCode::Char(key),
+            _ if rest.eq_ignore_ascii_case("space") => KeyCode::Char(' '),
+            _ => {
+                let lowercase = rest.to_ascii_lowercase();
+                let function = lowercase
+                    .strip_prefix('f')
+                    .and_then(|number| number.parse().ok())
+                    .filter(|number| (1..=24).contains(number));
+                match Self::NAMES.into_iter().find(|&(name, _)| name == lowercase) {
+                    Some((_, code)) => code,
+                    None => match function {
+                        Some(number) => KeyCode::F(number),
+                        None => return Err(ParseBindingError::UnknownKey(s.to_string())),
+                    },
+                }
+            }
+        };
+
+        let code = match code {
+            KeyCode::Char(key) if modifiers.contains(KeyModifiers::SHIFT) => {
+                modifiers.remove(KeyModifiers::SHIFT);
+                KeyCode::Char(key.to_ascii_uppercase())
+            }
+            code => code,
+        };
+        Ok(Self::new(code, modifiers))
+    }
+}
+
+impl Display for Key {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for (name, modifier) in [
+            ("ctrl", KeyModifiers::CONTROL),
+            ("alt", KeyModifiers::ALT),
+            ("shift", KeyModifiers::SHIFT),
+        ] {
+            if self.modifiers.contains(modifier) {
+                write!(f, "{name}-")?;
+            }
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("space"),
+            KeyCode::Char(key) => write!(f, "{key}"),
+            KeyCode::F(number) => write!(f, "f{number}"),
+            code => {
+                let name = Self::NAMES
+                    .into_iter()
+                    .find(|&(_, named)| named == code)
+                    .map_or("?", |(name, _)| name);
+                f.write_str(name)
+            }
+        }
+    }
+}
+
+/// Binding of a key to an action, written as `KEY=ACTION`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Binding {
+    pub key: Key,
+    pub action: Action,
+}
+
+impl FromStr for Binding {
+    type Err = ParseBindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        // The key may be `=` itself.
+        let (key, action) = s.rsplit_once('=').ok_or(ParseBindingError::MissingEquals)?;
+        let key = key.parse()?;
+        let action = Action::from_str(action, false)
+            .map_err(|_| ParseBindingError::UnknownAction(action.to_string()))?;
+        Ok(Self { key, action })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseBindingError {
+    MissingEquals,
+    UnknownKey(String),
+    UnknownAction(String),
+}
+
+impl Display for ParseBindingError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingEquals => f.write_str("expected `KEY=ACTION`"),
+            Self::UnknownKey(key) => write!(
+                f,
+                "unknown key `{key}` (expected a character or a name such as `left`, \
+                 `esc` or `f5`, optionally prefixed by `ctrl-`, `alt-` or `shift-`)"
+            ),
+            Self::UnknownAction(action) => write!(f, "unknown action `{action}`"),
+        }
+    }
+}
+
+impl Error for ParseBindingError {}
+
+/// Actions bound to keys, with a separate layer for edit mode.
+#[derive(Debug, Clone)]
+pub struct Keymap {
+    normal: HashMap<Key, Action>,
+    /// Bindings in edit mode, which take precedence over the normal ones.
+    editing: HashMap<Key, Action>,
+}
+
+impl Keymap {
+    /// Returns the default keymap with the given `bindings` on top, each
+    /// replacing the default binding of its key.
+    pub fn new(bindings: &[Binding]) -> Result<Self> {
+        let mut keymap = Self::default();
+        let mut bound = HashMap::new();
+        for &Binding { key, action } in bindings {
+            ensure!(
+                key.digit().is_none(),
+                "key `{key}` cannot be bound, as digits make up counts"
+            );
+            if let Some(other) = bound.insert(key, action)
+                && other != action
+            {
+                bail!("key `{key}` is bound to both `{other}` and `{action}`");
+            }
+            keymap.layer(action).insert(key, action);
+        }
+        ensure!(
+            keymap.normal.values().any(|&action| action == Action::Quit),
+            "no key is bound to `{}`",
+            Action::Quit
+        );
+        Ok(keymap)
+    }
+
+    fn layer(&mut self, action: Action) -> &mut HashMap<Key, Action> {
+        if action.is_editing() {
+            &mut self.editing
+        } else {
+            &mut self.normal
+        }
+    }
+
+    /// Returns the action bound to `key`, in edit mode if `editing`.
+    pub fn get(&self, key: Key, editing: bool) -> Option<Action> {
+        editing
+            .then(|| self.editing.get(&key))
+            .flatten()
+            .or_else(|| self.normal.get(&key))
+            .copied()
+    }
+
+    /// Returns the keys bound to `action`, characters first.
+    pub fn keys(&self, action: Action) -> Vec<Key> {
+        let mut keys: Vec<_> = self
+            .normal
+            .iter()
+            .chain(&self.editing)
+            .filter(|&(_, &bound)| bound == action)
+            .map(|(&key, _)| key)
+            .collect();
+        keys.sort_by_key(|key| {
+            let name = key.to_string();
+            (name.chars().count() > 1, name)
+        });
+        keys
+    }
+
+    /// Returns each action along with the keys bound to it, in the order
+    /// of the actions.
+    pub fn bindings(&self) -> impl Iterator<Item = (Action, Vec<Key>)> {
+        Action::value_variants()
+            .iter()
+            .map(|&action| (action, self.keys(action)))
+    }
+}
+
+impl Default for Keymap {
+    fn default() -> Self {
+        use Action::*;
+
+        let normal = [
+            (Key::char('j'), SlowDown),
+            (Key::char('k'), SpeedUp),
+            (Key::char('p'), Pause),
+            (Key::char('n'), Step),
+            (Key::char(','), Rewind),
+            (Key::char('.'), Replay),
+            (Key::char('f'), Flip),
+            (Key::char('r'), Reset),
+            (Key::char('s'), Save),
+            (Key::char('H'), PanLeft),
+            (Key::char('J'), PanDown),
+            (Key::char('K'), PanUp),
+            (Key::char('L'), PanRight),
+            (Key::code(KeyCode::Left), PanLeft),
+            (Key::code(KeyCode::Down), PanDown),
+            (Key::code(KeyCode::Up), PanUp),
+            (Key::code(KeyCode::Right), PanRight),
+            (Key::char('c'), Center),
+            (Key::char('e'), Edit),
+            (Key::char('?'), Help),
+            (Key::char('q'), Quit),
+            (Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL), Quit),
+        ];
+        let editing = [
+            (Key::char('h'), CursorLeft),
+            (Key::char('j'), CursorDown),
+            (Key::char('k'), CursorUp),
+            (Key::char('l'), CursorRight),
+            (Key::code(KeyCode::Left), CursorLeft),
+            (Key::code(KeyCode::Down), CursorDown),
+            (Key::code(KeyCode::Up), CursorUp),
+            (Key::code(KeyCode::Right), CursorRight),
+            (Key::char(' '), Toggle),
+            (Key::code(KeyCode::Esc), Edit),
+        ];
+
+        Self {
+            normal: normal.into_iter().collect(),
+            editing: editing.into_iter().collect(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn key(s: &str) -> Key {
+        s.parse().unwrap()
+    }
+
+    fn keymap(bindings: &[&str]) -> Result<Keymap> {
+        let bindings: Vec<Binding> = bindings.iter().map(|s| s.parse().unwrap()).collect();
+        Keymap::new(&bindings)
+    }
+
+    fn error(bindings: &[&str]) -> String {
+        format!("{:#}", keymap(bindings).unwrap_err())
+    }
+
+    #[test]
+    fn parses_keys() {
+        assert_eq!(key("q"), Key::char('q'));
+        assert_eq!(key("Q"), Key::char('Q'));
+        assert_eq!(key("-"), Key::char('-'));
+        assert_eq!(key("space"), Key::char(' '));
+        assert_eq!(key("LEFT"), Key::code(KeyCode::Left));
+        assert_eq!(key("f12"), Key::code(KeyCode::F(12)));
+        assert_eq!(key("shift-q"), Key::char('Q'));
+        assert_eq!(
+            key("ctrl-c"),
+            Key::new(KeyCode::Char('c'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            key("ctrl--"),
+            Key::new(KeyCode::Char('-'), KeyModifiers::CONTROL)
+        );
+        assert_eq!(
+            key("Ctrl-Alt-Shift-up"),
+            Key::new(
+                KeyCode::Up,
+                KeyModifiers::CONTROL | KeyModifiers::ALT | KeyModifiers::SHIFT
+            )
+        );
+    }
+
+    #[test]
+    fn rejects_unknown_keys() {
+        for s in ["", "ctrl-", "f0", "f25", "lefty", "ctrl-nope"] {
+            let error = s.parse::<Key>().unwrap_err();
+            assert_eq!(error, ParseBindingError::UnknownKey(s.to_string()));
+        }
+    }
+
+    #[test]
+    fn displays_parsable_keys() {
+        for s in [
+            "q",
+            "Q",
+            "-",
+            "space",
+            "left",
+            "f5",
+            "ctrl-c",
+            "alt-shift-pagedown",
+        ] {
+            assert_eq!(key(s).to_string(), s);
+        }
+    }
+
+    #[test]
+    fn parses_bindings() {
+        let binding: Binding = "==pause".parse().unwrap();
+        assert_eq!(binding.key, Key::char('='));
+        assert_eq!(binding.action, Action::Pause);
+        let error = "x".parse::<Binding>().unwrap_err();
+        assert_eq!(error, ParseBindingError::MissingEquals);
+        let error = "x=jump".parse::<Binding>().unwrap_err();
+        assert_eq!(error, ParseBindingError::UnknownAction("jump".to_string()));
+    }
+
+    #[test]
+    fn binds_keys_on_top_of_defaults() {
+        let keymap = keymap(&["x=pause", "q=step", "ctrl-q=quit", "space=flip"]).unwrap();
+        assert_eq!(keymap.get(key("x"), false), Some(Action::Pause));
+        assert_eq!(keymap.get(key("p"), false), Some(Action::Pause));
+        assert_eq!(keymap.get(key("q"), false), Some(Action::Step));
+        assert_eq!(keymap.get(key("ctrl-q"), false), Some(Action::Quit));
+        // Edit mode takes precedence, and falls back to the normal keys.
+        assert_eq!(keymap.get(key("space"), false), Some(Action::Flip));
+        assert_eq!(keymap.get(key("space"), true), Some(Action::Toggle));
+        assert_eq!(keymap.get(key("x"), true), Some(Action::Pause));
+    }
+
+    #[test]
+    fn rejects_conflicting_bindings() {
+        let message = error(&["x=pause", "x=step"]);
+        assert!(message.contains("key `x` is bound to both `pause` and `step`"));
+        assert!(keymap(&["x=pause", "x=pause"]).is_ok());
+    }
+
+    #[test]
+    fn rejects_digits() {
+        assert!(error(&["5=pause"]).contains("key `5` cannot be bound"));
+        assert!(keymap(&["ctrl-5=pause"]).is_ok());
+    }
+
+    #[test]
+    fn requires_a_key_to_quit() {
+        let message = error(&["q=pause", "ctrl-c=pause"]);
+        assert!(message.contains("no key is bound to `quit`"));
+    }
+}
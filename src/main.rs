@@ -1,7 +1,10 @@
+use self::biosquare::BioSquare;
 use self::cli::Args;
 use self::genesis::Genesis;
-use self::screen::Screen;
-use self::signal::LISTENER;
+use self::headless::Headless;
+use self::keymap::Keymap;
+use self::pattern::Pattern;
+use self::screen::{Screen, Settings, fit};
 use anyhow::Result;
 use eoe::QuitOnError;
 use matreex::Shape;
@@ -9,20 +12,65 @@ use std::io::stdout;
 
 mod biosquare;
 mod cli;
+mod command;
+mod config;
+mod engine;
 mod filter;
 mod genesis;
+mod headless;
+mod history;
+mod keymap;
+mod pattern;
+mod rule;
 mod screen;
-mod signal;
+mod stability;
+mod topology;
+mod viewport;
 
 fn run() -> Result<()> {
-    LISTENER.setup();
-
     let args = Args::parse();
-    let shape = Shape::new(args.nrows, args.ncols);
-    let genesis = Genesis::new(shape).random(args.density, args.seed)?;
+    let keymap = Keymap::new(&args.bindings)?;
+    let shape = if args.fit {
+        fit(&args.filter, args.show_stats)?
+    } else {
+        Shape::new(args.nrows, args.ncols)
+    };
+    let genesis = Genesis::new(shape);
+    let (genesis, rule) = match args.pattern {
+        None => (genesis.random(args.density, args.seed)?, args.rule),
+        Some(path) => {
+            let pattern = Pattern::read(path, shape)?;
+            let rule = args.rule.or(pattern.rule());
+            (genesis.pattern(&pattern, args.offset)?, rule)
+        }
+    };
+    let rule = rule.unwrap_or_default();
+    let topology = args.topology.unwrap_or(args.engine.topology());
+    let engine = args
+        .engine
+        .build(genesis, rule, topology, args.threads, args.memory_max)?;
+    let mut biosquare = BioSquare::new(engine);
+    biosquare.jump(args.jump);
     let output = stdout().lock();
 
-    Screen::new(genesis, args.fps_max, args.show_stats, args.filter, output)?.run()?;
+    if let Some(generations) = args.headless {
+        return Headless::new(biosquare, generations, args.save_on_exit, output).run();
+    }
+
+    let settings = Settings {
+        fps_max: args.fps_max,
+        step: args.step,
+        show_stats: args.show_stats,
+        history: args.history,
+        follow: args.follow,
+        full_redraw: args.full_redraw,
+        save_on_exit: args.save_on_exit,
+        on_stable: args.on_stable,
+        density: args.density,
+        keymap: keymap.clone(),
+    };
+    let commands = command::listen(keymap);
+    Screen::new(biosquare, settings, args.filter, commands, output)?.run()?;
 
     Ok(())
 }
@@ -0,0 +1,182 @@
+use crate::rule::Rule;
+use anyhow::{Context, Result, ensure};
+use matreex::Shape;
+use std::ffi::OsStr;
+use std::fs;
+use std::path::Path;
+
+mod life106;
+mod plaintext;
+mod rle;
+
+/// A finite arrangement of cells, as stored in a pattern file.
+///
+/// Only the alive cells are kept, so that a few cells far apart take little
+/// memory however large the pattern.
+#[derive(Debug)]
+pub struct Pattern {
+    shape: Shape,
+    /// Locations of the alive cells within `shape`, row by row.
+    alive: Vec<(usize, usize)>,
+    rule: Option<Rule>,
+}
+
+impl Pattern {
+    /// Creates a pattern of the given shape, alive at the given locations.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a location lies beyond `shape`.
+    pub fn new(shape: Shape, mut alive: Vec<(usize, usize)>, rule: Option<Rule>) -> Self {
+        assert!(
+            alive
+                .iter()
+                .all(|&(row, col)| row < shape.nrows && col < shape.ncols),
+            "alive cell beyond the pattern"
+        );
+        alive.sort_unstable();
+        alive.dedup();
+        Self { shape, alive, rule }
+    }
+
+    /// Reads a pattern from the given file, in a format detected from its
+    /// header or extension.
+    ///
+    /// Patterns larger than `bounds` are rejected before their cells are
+    /// allocated, whatever size they declare.
+    pub fn read<P>(path: P, bounds: Shape) -> Result<Self>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let text = fs::read_to_string(path)
+            .with_context(|| format!("failed to read pattern `{}`", path.display()))?;
+        let parse = match Format::detect(path, &text) {
+            Format::Rle => rle::parse,
+            Format::Plaintext => plaintext::parse,
+            Format::Life106 => life106::parse,
+        };
+        parse(&text, bounds).with_context(|| format!("invalid pattern `{}`", path.display()))
+    }
+
+    /// Writes the pattern to the given file, in a format chosen by its
+    /// extension (RLE unless it is `.cells`, `.lif` or `.life`).
+    ///
+    /// Each of `comments` is written as a separate comment line.
+    pub fn write<P>(&self, path: P, comments: &[String]) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let path = path.as_ref();
+        let format = Format::from_extension(path).unwrap_or(Format::Rle);
+        let text = match format {
+            Format::Rle => rle::write(self, comments),
+            Format::Plaintext => plaintext::write(self, comments)?,
+            Format::Life106 => life106::write(self, comments),
+        };
+        fs::write(path, text)
+            .with_context(|| format!("failed to write pattern `{}`", path.display()))
+    }
+
+    /// Returns the locations of the alive cells, row by row.
+    pub fn alive(&self) -> &[(usize, usize)] {
+        &self.alive
+    }
+
+    pub fn shape(&self) -> Shape {
+        self.shape
+    }
+
+    /// Returns the rule the pattern was designed for, if specified.
+    pub fn rule(&self) -> Option<Rule> {
+        self.rule
+    }
+}
+
+#[cfg(test)]
+impl Pattern {
+    /// Builds a pattern from rows drawn with `O` for alive cells and `.` for
+    /// dead ones, padded with dead cells to the widest row.
+    fn drawn(rows: &[&str], rule: Option<Rule>) -> Self {
+        let ncols = rows.iter().map(|row| row.len()).max().unwrap_or(0);
+        let alive = rows
+            .iter()
+            .enumerate()
+            .flat_map(|(row, cells)| {
+                cells
+                    .bytes()
+                    .enumerate()
+                    .filter(|&(_, cell)| cell == b'O')
+                    .map(move |(col, _)| (row, col))
+            })
+            .collect();
+        Self::new(Shape::new(rows.len(), ncols), alive, rule)
+    }
+
+    /// Returns the shape along with the alive cells, to compare patterns
+    /// regardless of their rule.
+    fn cells(&self) -> (Shape, &[(usize, usize)]) {
+        (self.shape, &self.alive)
+    }
+}
+
+/// Returns an error if a pattern of the given shape does not fit in
+/// `bounds`.
+pub fn ensure_fits(shape: Shape, bounds: Shape) -> Result<()> {
+    ensure!(
+        shape.nrows <= bounds.nrows && shape.ncols <= bounds.ncols,
+        "pattern of {}×{} cells does not fit in a world of {}×{} cells",
+        shape.nrows,
+        shape.ncols,
+        bounds.nrows,
+        bounds.ncols,
+    );
+    Ok(())
+}
+
+fn parse_rule(value: &str) -> Result<Rule> {
+    // Strip bounded grid suffixes such as `:T100,100`, which are not
+    // meaningful here.
+    let value = value.split(':').next().unwrap_or(value).trim();
+    value
+        .parse()
+        .with_context(|| format!("invalid rule `{value}`"))
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Format {
+    Rle,
+    Plaintext,
+    Life106,
+}
+
+impl Format {
+    fn detect(path: &Path, text: &str) -> Self {
+        // `.lif` and `.life` are shared by Life 1.05 and Life 1.06, so the
+        // header takes precedence over the extension.
+        if text.trim_start().starts_with(life106::HEADER) {
+            return Self::Life106;
+        }
+
+        if let Some(format @ (Self::Rle | Self::Plaintext)) = Self::from_extension(path) {
+            return format;
+        }
+
+        let first = text.lines().map(str::trim).find(|line| !line.is_empty());
+        match first {
+            Some(line) if line.starts_with('!') => Self::Plaintext,
+            Some(line) if line.chars().all(|c| matches!(c, '.' | 'O' | '*')) => Self::Plaintext,
+            _ => Self::Rle,
+        }
+    }
+
+    fn from_extension(path: &Path) -> Option<Self> {
+        let extension = path.extension().and_then(OsStr::to_str)?;
+        match extension.to_ascii_lowercase().as_str() {
+            "rle" => Some(Self::Rle),
+            "cells" => Some(Self::Plaintext),
+            "lif" | "life" => Some(Self::Life106),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,139 @@
+//! Reader for the [Life 1.06] format.
+//!
+//! [Life 1.06]: https://conwaylife.com/wiki/Life_1.06
+
+use super::{Pattern, ensure_fits, parse_rule};
+use anyhow::{Context, Result, ensure};
+use matreex::Shape;
+
+pub const HEADER: &str = "#Life 1.06";
+
+pub fn parse(text: &str, bounds: Shape) -> Result<Pattern> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()));
+
+    let header = lines.next().map(|(_, line)| line);
+    ensure!(header == Some(HEADER), "line 1: expected `{HEADER}`");
+
+    let mut coords = Vec::new();
+    let mut rule = None;
+    for (lineno, line) in lines {
+        if let Some(value) = line.strip_prefix("#D Rule:") {
+            rule = Some(parse_rule(value).with_context(|| format!("line {lineno}"))?);
+            continue;
+        }
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        let mut fields = line.split_whitespace();
+        let mut coord = || -> Result<isize> {
+            let field = fields
+                .next()
+                .with_context(|| format!("line {lineno}: expected `x y`"))?;
+            field
+                .parse()
+                .with_context(|| format!("line {lineno}: invalid coordinate `{field}`"))
+        };
+        let x = coord()?;
+        let y = coord()?;
+        ensure!(
+            fields.next().is_none(),
+            "line {lineno}: unexpected trailing content"
+        );
+        coords.push((y, x));
+    }
+
+    let Some(min_row) = coords.iter().map(|&(row, _)| row).min() else {
+        return Ok(Pattern::new(Shape::new(0, 0), Vec::new(), rule));
+    };
+    let min_col = coords.iter().map(|&(_, col)| col).min().unwrap_or(0);
+    let max_row = coords.iter().map(|&(row, _)| row).max().unwrap_or(0);
+    let max_col = coords.iter().map(|&(_, col)| col).max().unwrap_or(0);
+
+    // The span of the coordinates may exceed any world, or even `usize`.
+    let nrows = max_row.abs_diff(min_row).saturating_add(1);
+    let ncols = max_col.abs_diff(min_col).saturating_add(1);
+    let shape = Shape::new(nrows, ncols);
+    ensure_fits(shape, bounds)?;
+    let alive = coords
+        .into_iter()
+        .map(|(row, col)| (row.abs_diff(min_row), col.abs_diff(min_col)))
+        .collect();
+
+    Ok(Pattern::new(shape, alive, rule))
+}
+
+pub fn write(pattern: &Pattern, comments: &[String]) -> String {
+    let mut text = format!("{HEADER}\n");
+    if let Some(rule) = pattern.rule {
+        text.push_str(&format!("#D Rule: {rule}\n"));
+    }
+    for comment in comments {
+        text.push_str(&format!("#D {comment}\n"));
+    }
+
+    for &(row, col) in &pattern.alive {
+        text.push_str(&format!("{col} {row}\n"));
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Shape = Shape {
+        nrows: 40,
+        ncols: 40,
+    };
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text, BOUNDS).unwrap_err())
+    }
+
+    #[test]
+    fn round_trip() {
+        let rule = "B36/S23".parse().ok();
+        let pattern = Pattern::drawn(&[".O..", "...O", "OOO.", "....", "O..O"], rule);
+        let text = write(&pattern, &["Generation 7".to_string()]);
+        let parsed = parse(&text, BOUNDS).unwrap();
+        assert_eq!(parsed.cells(), pattern.cells());
+        assert_eq!(parsed.rule, pattern.rule);
+    }
+
+    #[test]
+    fn parses_glider_around_origin() {
+        let text = "#Life 1.06\n#D Glider\n0 -1\n1 0\n-1 1\n0 1\n1 1\n";
+        let pattern = parse(text, BOUNDS).unwrap();
+        assert_eq!(
+            pattern.cells(),
+            Pattern::drawn(&[".O.", "..O", "OOO"], None).cells()
+        );
+    }
+
+    #[test]
+    fn parses_empty_pattern() {
+        let pattern = parse("#Life 1.06\n", BOUNDS).unwrap();
+        assert_eq!(pattern.shape(), Shape::new(0, 0));
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!(error("#Life 1.05\n0 0\n").contains("expected `#Life 1.06`"));
+        assert!(error("#Life 1.06\n0\n").contains("line 2: expected `x y`"));
+        assert!(error("#Life 1.06\n0 y\n").contains("line 2: invalid coordinate `y`"));
+        assert!(error("#Life 1.06\n0 0 0\n").contains("line 2: unexpected trailing content"));
+        assert!(error("#Life 1.06\n#D Rule: B9/S23\n").contains("line 2: invalid rule `B9/S23`"));
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_bounds() {
+        assert!(error("#Life 1.06\n0 0\n40 0\n").contains("does not fit"));
+        let extremes = format!("#Life 1.06\n{} {}\n{} {}\n", isize::MIN, 0, isize::MAX, 0);
+        assert!(error(&extremes).contains("does not fit"));
+    }
+}
@@ -0,0 +1,157 @@
+//! Reader for the [plaintext] format, commonly with the `.cells` extension.
+//!
+//! [plaintext]: https://conwaylife.com/wiki/Plaintext
+
+use super::{Pattern, ensure_fits, parse_rule};
+use anyhow::{Context, Result, bail, ensure};
+use matreex::Shape;
+
+/// Maximum number of cells written, as dead cells are written one by one
+/// unlike in the other formats.
+const LENGTH_MAX: usize = 1 << 30;
+
+pub fn parse(text: &str, bounds: Shape) -> Result<Pattern> {
+    // Number of cells in each row, alive or dead.
+    let mut widths = Vec::new();
+    let mut alive = Vec::new();
+    let mut rule = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let lineno = index + 1;
+        let line = line.trim_end();
+        if let Some(comment) = line.strip_prefix('!') {
+            if let Some(value) = comment.strip_prefix("Rule:") {
+                rule = Some(parse_rule(value).with_context(|| format!("line {lineno}"))?);
+            }
+            continue;
+        }
+
+        let row = widths.len();
+        for (col, c) in line.chars().enumerate() {
+            match c {
+                '.' => {}
+                'O' | '*' => alive.push((row, col)),
+                c => bail!("line {lineno}: unexpected character `{c}`"),
+            }
+        }
+        widths.push(line.chars().count());
+    }
+
+    while widths.last() == Some(&0) {
+        widths.pop();
+    }
+
+    let ncols = widths.iter().copied().max().unwrap_or(0);
+    let shape = Shape::new(widths.len(), ncols);
+    ensure_fits(shape, bounds)?;
+
+    Ok(Pattern::new(shape, alive, rule))
+}
+
+pub fn write(pattern: &Pattern, comments: &[String]) -> Result<String> {
+    // Each row is written up to its last alive cell.
+    let rows = pattern.alive.chunk_by(|(row, _), (next, _)| row == next);
+    let length = rows
+        .clone()
+        .map(|cells| cells[cells.len() - 1].1 + 1)
+        .fold(pattern.shape.nrows, usize::saturating_add);
+    ensure!(
+        length <= LENGTH_MAX,
+        "pattern of {}×{} cells is too large for the plaintext format",
+        pattern.shape.nrows,
+        pattern.shape.ncols,
+    );
+
+    let mut text = String::new();
+    if let Some(rule) = pattern.rule {
+        text.push_str(&format!("!Rule: {rule}\n"));
+    }
+    for comment in comments {
+        text.push_str(&format!("!{comment}\n"));
+    }
+
+    let mut next_row = 0;
+    for cells in rows {
+        let (row, _) = cells[0];
+        text.push_str(&"\n".repeat(row - next_row));
+        let mut next_col = 0;
+        for &(_, col) in cells {
+            text.push_str(&".".repeat(col - next_col));
+            text.push('O');
+            next_col = col + 1;
+        }
+        text.push('\n');
+        next_row = row + 1;
+    }
+    text.push_str(&"\n".repeat(pattern.shape.nrows - next_row));
+
+    Ok(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Shape = Shape {
+        nrows: 40,
+        ncols: 40,
+    };
+
+    #[test]
+    fn round_trip() {
+        let rule = "B36/S23".parse().ok();
+        let pattern = Pattern::drawn(&[".O..", "...O", "OOO.", "....", "O..O"], rule);
+        let text = write(&pattern, &["Generation 7".to_string()]).unwrap();
+        let parsed = parse(&text, BOUNDS).unwrap();
+        assert_eq!(parsed.cells(), pattern.cells());
+        assert_eq!(parsed.rule, pattern.rule);
+    }
+
+    #[test]
+    fn parses_glider() {
+        let text = "!Name: Glider\n!\n.O\n..*\nOOO\n\n";
+        let pattern = parse(text, BOUNDS).unwrap();
+        assert_eq!(
+            pattern.cells(),
+            Pattern::drawn(&[".O.", "..O", "OOO"], None).cells()
+        );
+        assert_eq!(pattern.rule, None);
+    }
+
+    #[test]
+    fn rejects_unexpected_characters() {
+        let error = parse("!Name: Glider\n.O.\n..o\n", BOUNDS).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("line 3: unexpected character `o`")
+        );
+        let error = parse(
+            "!Rule: B9/S23
+.O.
+",
+            BOUNDS,
+        )
+        .unwrap_err();
+        assert!(format!("{error:#}").contains("line 1: invalid rule `B9/S23`"));
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_bounds() {
+        let text = "O".repeat(41);
+        let error = parse(&text, BOUNDS).unwrap_err();
+        assert!(error.to_string().contains("does not fit"));
+    }
+
+    #[test]
+    fn rejects_writing_patterns_too_large() {
+        let far = 1 << 40;
+        let pattern = Pattern::new(Shape::new(far, far), vec![(far - 1, far - 1)], None);
+        let error = write(&pattern, &[]).unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .contains("too large for the plaintext format")
+        );
+    }
+}
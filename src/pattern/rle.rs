@@ -0,0 +1,291 @@
+//! Reader for the [RLE] format.
+//!
+//! [RLE]: https://conwaylife.com/wiki/Run_Length_Encoded
+
+use super::{Pattern, ensure_fits, parse_rule};
+use crate::rule::Rule;
+use anyhow::{Context, Result, anyhow, bail, ensure};
+use matreex::Shape;
+
+pub fn parse(text: &str, bounds: Shape) -> Result<Pattern> {
+    let mut lines = text
+        .lines()
+        .enumerate()
+        .map(|(index, line)| (index + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty());
+
+    let mut rule = None;
+    let (lineno, header) = loop {
+        let (lineno, line) = lines
+            .next()
+            .context("missing header line `x = .., y = ..`")?;
+        match line.strip_prefix('#') {
+            None => break (lineno, line),
+            Some(comment) => {
+                if let Some(value) = comment.strip_prefix('r') {
+                    rule = Some(parse_rule(value).with_context(|| format!("line {lineno}"))?);
+                }
+            }
+        }
+    };
+
+    let header = parse_header(header).with_context(|| format!("line {lineno}"))?;
+    let rule = header.rule.or(rule);
+    ensure_fits(header.shape, bounds)?;
+    let mut alive = Vec::new();
+
+    let mut row: usize = 0;
+    let mut col: usize = 0;
+    let mut count: Option<usize> = None;
+
+    'outer: for (lineno, line) in lines {
+        for c in line.chars().filter(|c| !c.is_whitespace()) {
+            if let Some(digit) = c.to_digit(10) {
+                count = count
+                    .unwrap_or(0)
+                    .checked_mul(10)
+                    .and_then(|count| count.checked_add(digit as usize))
+                    .map(Some)
+                    .with_context(|| format!("line {lineno}: run count overflow"))?;
+                continue;
+            }
+
+            let run = count.take().unwrap_or(1);
+            match c {
+                'b' | 'o' => {
+                    let end = col
+                        .checked_add(run)
+                        .with_context(|| format!("line {lineno}: column overflow"))?;
+                    ensure!(
+                        end <= header.shape.ncols,
+                        "line {lineno}: row {} exceeds the declared width of {}",
+                        row + 1,
+                        header.shape.ncols,
+                    );
+                    if c == 'o' {
+                        ensure!(
+                            row < header.shape.nrows,
+                            "line {lineno}: pattern exceeds the declared height of {}",
+                            header.shape.nrows,
+                        );
+                        alive.extend((col..end).map(|col| (row, col)));
+                    }
+                    col = end;
+                }
+                '$' => {
+                    row = row
+                        .checked_add(run)
+                        .with_context(|| format!("line {lineno}: row overflow"))?;
+                    col = 0;
+                }
+                '!' => break 'outer,
+                c => bail!("line {lineno}: unexpected character `{c}`"),
+            }
+        }
+    }
+
+    ensure!(count.is_none(), "dangling run count at end of pattern");
+
+    Ok(Pattern::new(header.shape, alive, rule))
+}
+
+struct Header {
+    shape: Shape,
+    rule: Option<Rule>,
+}
+
+fn parse_header(line: &str) -> Result<Header> {
+    let mut nrows = None;
+    let mut ncols = None;
+    let mut rule = None;
+
+    let mut rest = line;
+    while !rest.trim().is_empty() {
+        let (key, tail) = rest
+            .split_once('=')
+            .ok_or_else(|| anyhow!("expected `key = value`, found `{}`", rest.trim()))?;
+        let key = key.trim();
+
+        // The rule may contain commas itself, e.g. `B3/S23:T100,100`, so it
+        // extends to the end of the line.
+        let (value, tail) = match key {
+            "rule" => (tail, ""),
+            _ => tail.split_once(',').unwrap_or((tail, "")),
+        };
+        let value = value.trim();
+        rest = tail;
+
+        match key {
+            "x" => ncols = Some(parse_dimension(key, value)?),
+            "y" => nrows = Some(parse_dimension(key, value)?),
+            "rule" => rule = Some(parse_rule(value)?),
+            key => bail!("unknown header key `{key}`"),
+        }
+    }
+
+    let nrows = nrows.context("missing `y` in header")?;
+    let ncols = ncols.context("missing `x` in header")?;
+    let shape = Shape::new(nrows, ncols);
+
+    Ok(Header { shape, rule })
+}
+
+fn parse_dimension(key: &str, value: &str) -> Result<usize> {
+    value
+        .parse()
+        .with_context(|| format!("invalid `{key}` value `{value}`"))
+}
+
+pub fn write(pattern: &Pattern, comments: &[String]) -> String {
+    let mut text = String::new();
+    for comment in comments {
+        text.push_str(&format!("#C {comment}\n"));
+    }
+
+    let shape = pattern.shape();
+    text.push_str(&format!("x = {}, y = {}", shape.ncols, shape.nrows));
+    if let Some(rule) = pattern.rule {
+        text.push_str(&format!(", rule = {rule}"));
+    }
+    text.push('\n');
+
+    // Runs are read off the alive cells, so that dead cells cost nothing
+    // however far apart the alive ones are.
+    let mut body = Body::default();
+    let (mut row, mut col) = (0, 0);
+    let runs = pattern
+        .alive
+        .chunk_by(|&(row, col), &(next_row, next_col)| next_row == row && next_col == col + 1);
+    for run in runs {
+        let (run_row, run_col) = run[0];
+        if run_row > row {
+            body.push(run_row - row, '$');
+            (row, col) = (run_row, 0);
+        }
+        if run_col > col {
+            body.push(run_col - col, 'b');
+        }
+        body.push(run.len(), 'o');
+        col = run_col + run.len();
+    }
+    body.push(1, '!');
+
+    text.push_str(&body.finish());
+    text
+}
+
+#[derive(Default)]
+struct Body {
+    lines: String,
+    line: String,
+}
+
+impl Body {
+    const LINE_WIDTH: usize = 70;
+
+    fn push(&mut self, run: usize, tag: char) {
+        let token = match run {
+            1 => tag.to_string(),
+            run => format!("{run}{tag}"),
+        };
+        if self.line.len() + token.len() > Self::LINE_WIDTH {
+            self.lines.push_str(&self.line);
+            self.lines.push('\n');
+            self.line.clear();
+        }
+        self.line.push_str(&token);
+    }
+
+    fn finish(mut self) -> String {
+        self.lines.push_str(&self.line);
+        self.lines.push('\n');
+        self.lines
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: Shape = Shape {
+        nrows: 40,
+        ncols: 40,
+    };
+
+    fn error(text: &str) -> String {
+        format!("{:#}", parse(text, BOUNDS).unwrap_err())
+    }
+
+    #[test]
+    fn round_trip() {
+        let rule = "B36/S23".parse().ok();
+        let pattern = Pattern::drawn(&[".O..", "...O", "OOO.", "....", "O..O"], rule);
+        let text = write(&pattern, &["Generation 7".to_string()]);
+        let parsed = parse(&text, BOUNDS).unwrap();
+        assert_eq!(parsed.cells(), pattern.cells());
+        assert_eq!(parsed.rule, pattern.rule);
+    }
+
+    #[test]
+    fn long_runs_wrap_across_lines() {
+        let row = "O.".repeat(20);
+        let pattern = Pattern::drawn(&[&row, &row], None);
+        let text = write(&pattern, &[]);
+        assert!(text.lines().count() > 2);
+        assert_eq!(parse(&text, BOUNDS).unwrap().cells(), pattern.cells());
+    }
+
+    #[test]
+    fn parses_glider() {
+        let text = "#N Glider\n#C A comment\nx = 3, y = 3, rule = B3/S23:T10,10\nbo$2bo$\n3o!";
+        let pattern = parse(text, BOUNDS).unwrap();
+        assert_eq!(
+            pattern.cells(),
+            Pattern::drawn(&[".O.", "..O", "OOO"], None).cells()
+        );
+        assert_eq!(pattern.rule, "B3/S23".parse().ok());
+    }
+
+    #[test]
+    fn takes_rule_from_comment() {
+        let pattern = parse("#r B36/S23\nx = 1, y = 1\no!", BOUNDS).unwrap();
+        assert_eq!(pattern.rule, "B36/S23".parse().ok());
+    }
+
+    #[test]
+    fn rejects_malformed_patterns() {
+        assert!(error("").contains("missing header"));
+        assert!(error("x = 3\n3o!").contains("missing `y`"));
+        assert!(error("x = 3, y = 1, z = 2\n3o!").contains("unknown header key `z`"));
+        assert!(error("x = three, y = 1\n3o!").contains("invalid `x` value `three`"));
+        assert!(error("x = 1, y = 1, rule = B9\no!").contains("invalid rule"));
+        assert!(error("x = 2, y = 1\n3o!").contains("exceeds the declared width"));
+        assert!(error("x = 1, y = 1\no$o!").contains("exceeds the declared height"));
+        assert!(error("x = 1, y = 1\nx!").contains("unexpected character `x`"));
+        assert!(error("x = 1, y = 1\no3").contains("dangling run count"));
+        assert!(error("x = 1, y = 1\n99999999999999999999o!").contains("run count overflow"));
+        assert!(
+            error("x = 2, y = 1\nob18446744073709551615o!").contains("line 2: column overflow")
+        );
+        assert!(error("x = 1, y = 1\n18446744073709551615$$o!").contains("line 2: row overflow"));
+    }
+
+    #[test]
+    fn rejects_patterns_larger_than_bounds() {
+        let message = error("x = 3000000000, y = 3000000000\no!");
+        assert!(message.contains("does not fit in a world of 40×40 cells"));
+        assert!(error("x = 41, y = 1\no!").contains("does not fit"));
+    }
+
+    #[test]
+    fn writes_cells_far_apart() {
+        let far = 1 << 40;
+        let alive = vec![(0, 1), (0, 2), (far, far - 1)];
+        let pattern = Pattern::new(Shape::new(far + 1, far), alive, None);
+        let text = write(&pattern, &[]);
+        assert_eq!(
+            text,
+            "x = 1099511627776, y = 1099511627777\nb2o1099511627776$1099511627775bo!\n"
+        );
+    }
+}
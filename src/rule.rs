@@ -0,0 +1,184 @@
+use crate::biosquare::Cell;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// Birth/survival rule of a Life-like cellular automaton.
+///
+/// Each bit `n` of `birth` (resp. `survival`) is set if a dead (resp. alive)
+/// cell with `n` alive neighbors is alive in the next generation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Rule {
+    birth: u16,
+    survival: u16,
+}
+
+impl Rule {
+    /// Conway's Game of Life, `B3/S23`.
+    pub const CONWAY: Self = Self {
+        birth: 1 << 3,
+        survival: 1 << 2 | 1 << 3,
+    };
+
+    /// Returns `true` if a dead cell with `neighbors` alive neighbors is born.
+    pub fn birth(self, neighbors: usize) -> bool {
+        self.birth >> neighbors & 1 == 1
+    }
+
+    /// Returns `true` if an alive cell with `neighbors` alive neighbors
+    /// survives.
+    pub fn survival(self, neighbors: usize) -> bool {
+        self.survival >> neighbors & 1 == 1
+    }
+
+    pub fn apply(self, cell: Cell, neighbors: usize) -> Cell {
+        let mask = match cell {
+            Cell::Dead => self.birth,
+            Cell::Alive => self.survival,
+        };
+        Cell::from(mask >> neighbors & 1 == 1)
+    }
+}
+
+impl Default for Rule {
+    fn default() -> Self {
+        Self::CONWAY
+    }
+}
+
+impl FromStr for Rule {
+    type Err = ParseRuleError;
+
+    /// Parses a rulestring in either `B36/S23` or `23/36` notation.
+    ///
+    /// In the former, the `B` and `S` parts may appear in any order and are
+    /// case-insensitive. In the latter, survival counts come first.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let (lhs, rhs) = s.split_once('/').ok_or(ParseRuleError::MissingSlash)?;
+
+        let mut lhs = lhs.chars();
+        let mut rhs = rhs.chars();
+        let prefixes = (
+            lhs.clone().next().map(|c| c.to_ascii_uppercase()),
+            rhs.clone().next().map(|c| c.to_ascii_uppercase()),
+        );
+
+        let (birth, survival) = match prefixes {
+            (Some('B'), Some('S')) => {
+                lhs.next();
+                rhs.next();
+                (lhs, rhs)
+            }
+            (Some('S'), Some('B')) => {
+                lhs.next();
+                rhs.next();
+                (rhs, lhs)
+            }
+            (Some('B' | 'S'), _) | (_, Some('B' | 'S')) => {
+                return Err(ParseRuleError::MismatchedPrefix);
+            }
+            _ => (rhs, lhs),
+        };
+
+        Ok(Self {
+            birth: parse_counts(birth)?,
+            survival: parse_counts(survival)?,
+        })
+    }
+}
+
+impl Display for Rule {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.write_str("B")?;
+        fmt_counts(self.birth, f)?;
+        f.write_str("/S")?;
+        fmt_counts(self.survival, f)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseRuleError {
+    MissingSlash,
+    MismatchedPrefix,
+    InvalidCount(char),
+}
+
+impl Display for ParseRuleError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::MissingSlash => {
+                f.write_str("expected `B<counts>/S<counts>` or `<counts>/<counts>`")
+            }
+            Self::MismatchedPrefix => f.write_str("expected one `B` part and one `S` part"),
+            Self::InvalidCount(c) => write!(f, "invalid neighbor count `{c}` (expected 0-8)"),
+        }
+    }
+}
+
+impl Error for ParseRuleError {}
+
+fn parse_counts<I>(mut chars: I) -> Result<u16, ParseRuleError>
+where
+    I: Iterator<Item = char>,
+{
+    chars.try_fold(0, |mask, c| match c.to_digit(10) {
+        Some(n @ 0..=8) => Ok(mask | 1 << n),
+        _ => Err(ParseRuleError::InvalidCount(c)),
+    })
+}
+
+fn fmt_counts(mask: u16, f: &mut Formatter<'_>) -> fmt::Result {
+    (0..=8)
+        .filter(|n| mask >> n & 1 == 1)
+        .try_for_each(|n| write!(f, "{n}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(s: &str) -> Rule {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn parses_both_notations() {
+        assert_eq!(rule("B3/S23"), Rule::CONWAY);
+        assert_eq!(rule("23/3"), Rule::CONWAY);
+        assert_eq!(rule("S23/B3"), Rule::CONWAY);
+        assert_eq!(rule("b3/s23"), Rule::CONWAY);
+        assert_eq!(rule(" B3/S23 "), Rule::CONWAY);
+        assert_eq!(rule("B36/S23"), rule("23/36"));
+        assert_eq!(rule("/3"), rule("B3/S"));
+        assert_eq!(rule("B/S"), rule("/"));
+    }
+
+    #[test]
+    fn displays_in_b_s_notation() {
+        assert_eq!(rule("23/36").to_string(), "B36/S23");
+        assert_eq!(rule("S8/B0").to_string(), "B0/S8");
+        assert_eq!(rule("/").to_string(), "B/S");
+    }
+
+    #[test]
+    fn rejects_malformed_rules() {
+        let error = |s: &str| s.parse::<Rule>().unwrap_err();
+        assert_eq!(error("B3S23"), ParseRuleError::MissingSlash);
+        assert_eq!(error(""), ParseRuleError::MissingSlash);
+        assert_eq!(error("B3/23"), ParseRuleError::MismatchedPrefix);
+        assert_eq!(error("3/S23"), ParseRuleError::MismatchedPrefix);
+        assert_eq!(error("B3/B23"), ParseRuleError::MismatchedPrefix);
+        assert_eq!(error("B9/S"), ParseRuleError::InvalidCount('9'));
+        assert_eq!(error("23/3x"), ParseRuleError::InvalidCount('x'));
+    }
+
+    #[test]
+    fn applies_counts() {
+        let conway = Rule::CONWAY;
+        assert_eq!(conway.apply(Cell::Dead, 3), Cell::Alive);
+        assert_eq!(conway.apply(Cell::Dead, 2), Cell::Dead);
+        assert_eq!(conway.apply(Cell::Alive, 2), Cell::Alive);
+        assert_eq!(conway.apply(Cell::Alive, 4), Cell::Dead);
+    }
+}
@@ -1,14 +1,36 @@
-use crate::biosquare::{BioSquare, Cell};
-use crate::filter::Filter;
-use crate::signal;
+use self::canvas::Canvas;
+use self::trend::Trend;
+use crate::biosquare::{BioSquare, Cell, Rect};
+use crate::command::{Command, Stroke};
+use crate::filter::{Filter, Patch};
+use crate::genesis::Density;
+use crate::history::History;
+use crate::keymap::{Action, Keymap};
+use crate::stability::{Detector, OnStable};
+use crate::topology::Topology;
+use crate::viewport::Viewport;
 use anyhow::Result;
 use crossterm::style::Stylize;
-use crossterm::{QueueableCommand, cursor, style, terminal};
+use crossterm::{QueueableCommand, cursor, event, style, terminal};
 use eoe::QuitOnError;
-use matreex::Matrix;
+use matreex::{Index, Shape};
 use rand::rngs::ThreadRng;
-use std::io::Write;
-use std::time::{Duration, Instant};
+use std::io::{self, Write};
+use std::iter;
+use std::path::PathBuf;
+use std::sync::mpsc::{Receiver, RecvTimeoutError};
+use std::time::{Duration, Instant, SystemTime};
+
+/// Number of terminal rows taken by the statistics, including the gap above
+/// them.
+const STATS_HEIGHT: u16 = 20;
+
+/// Widths of the names and the values of the statistics, in columns.
+const KEY_WIDTH: usize = 20;
+const VALUE_WIDTH: usize = 40;
+
+mod canvas;
+mod trend;
 
 #[derive(Debug)]
 pub struct Screen<F, O>
@@ -17,13 +39,32 @@ where
     O: Write,
 {
     biosquare: BioSquare,
-    genesis: Matrix<Cell>,
-    fps_max: FpsMax,
-    show_stats: bool,
+    genesis: BioSquare,
+    history: History,
+    detector: Detector,
+    /// Population over the last frames.
+    trend: Trend,
+    settings: Settings,
+    commands: Receiver<Command>,
+    paused: bool,
+    /// Number of generations left to evolve one per frame while paused.
+    steps: usize,
+    time_scale: TimeScale,
+    viewport: Viewport,
+    /// State of edit mode, if enabled.
+    editor: Option<Editor>,
+    /// Whether the key bindings are shown over the world.
+    showing_help: bool,
+    /// Path of the last file saved, or the reason it failed.
+    last_saved: Option<Result<PathBuf, String>>,
     timer: Timer,
+    scheduler: Scheduler,
     rng: ThreadRng,
     filter: F,
-    output: O,
+    canvas: Canvas,
+    output: Counted<O>,
+    /// Number of bytes written for the last frame.
+    frame_output: usize,
 }
 
 impl<F, O> Screen<F, O>
@@ -31,63 +72,175 @@ where
     F: Filter,
     O: Write,
 {
+    /// Creates a screen that carries out the `commands` it receives, as
+    /// from [`command::listen`](crate::command::listen).
     pub fn new(
-        genesis: Matrix<Cell>,
-        fps_max: FpsMax,
-        show_stats: bool,
+        mut biosquare: BioSquare,
+        settings: Settings,
         filter: F,
+        commands: Receiver<Command>,
         output: O,
     ) -> Result<Self> {
-        let biosquare = BioSquare::new(genesis.clone());
+        let genesis = biosquare.clone();
+        let history = History::new(settings.history, &mut biosquare);
+        let detector = Detector::new();
+        let mut trend = Trend::new(KEY_WIDTH + VALUE_WIDTH);
+        trend.push(biosquare.population());
+        let world = match biosquare.topology() {
+            Topology::Infinite => None,
+            _ => Some(biosquare.shape()),
+        };
+        let viewport = Viewport::new(world, settings.follow);
+        let paused = false;
+        let steps = 0;
+        let time_scale = TimeScale::default();
+        let editor = None;
+        let showing_help = false;
+        let last_saved = None;
         let timer = Timer::start();
+        let scheduler = Scheduler::start();
         let rng = rand::rng();
+        let canvas = Canvas::new();
+        let output = Counted::new(output);
+        let frame_output = 0;
 
         let mut tui = Self {
             biosquare,
             genesis,
-            fps_max,
-            show_stats,
+            history,
+            detector,
+            trend,
+            settings,
+            commands,
+            paused,
+            steps,
+            time_scale,
+            viewport,
+            editor,
+            showing_help,
+            last_saved,
             timer,
+            scheduler,
             rng,
             filter,
+            canvas,
             output,
+            frame_output,
         };
 
         tui.enter_alternate_screen()?;
+        tui.reflow()?;
 
         Ok(tui)
     }
 
     pub fn run(&mut self) -> Result<()> {
-        'outer: loop {
-            self.timer.tick();
+        self.run_until_quit()?;
 
-            self.wait_if_paused();
+        if let Some(path) = &self.settings.save_on_exit {
+            self.biosquare.save(path)?;
+        }
 
-            if signal::QUIT.get() {
-                break Ok(());
-            }
+        Ok(())
+    }
 
-            if signal::FLIP.take() {
-                self.random_flip();
-            }
+    fn run_until_quit(&mut self) -> Result<()> {
+        self.render()?;
 
-            if signal::RESET.take() {
-                self.reset();
+        loop {
+            for command in self.receive() {
+                if !self.execute(command)? {
+                    return Ok(());
+                }
             }
 
-            if let Err(error) = self.render() {
-                break Err(error);
+            // Pending steps are taken at the same pace as frames.
+            let frame = self.frame_duration();
+            if self.scheduler.is_due(frame) {
+                let generations = if !self.paused {
+                    Some(self.settings.step)
+                } else if self.steps > 0 {
+                    self.steps -= 1;
+                    Some(1)
+                } else {
+                    None
+                };
+                if let Some(generations) = generations {
+                    self.timer.tick();
+                    self.scheduler.next(frame);
+                    if !self.advance(generations) {
+                        return Ok(());
+                    }
+                }
             }
 
-            self.biosquare.evolve();
+            self.render()?;
+        }
+    }
 
-            while self.timer.frame().as_secs_f64() < self.frame_duration_min() {
-                if signal::QUIT.get() {
-                    break 'outer Ok(());
-                }
+    /// Waits for commands until the next frame is due, or indefinitely while
+    /// paused, and returns all that have arrived.
+    ///
+    /// Commands that can no longer be received amount to [`Command::Quit`].
+    fn receive(&mut self) -> Vec<Command> {
+        let first = if self.paused && self.steps == 0 {
+            let _paused = self.timer.pause();
+            self.commands
+                .recv()
+                .map_err(|_| RecvTimeoutError::Disconnected)
+        } else {
+            match self.scheduler.deadline(self.frame_duration()) {
+                Some(deadline) => self
+                    .commands
+                    .recv_timeout(deadline.saturating_duration_since(Instant::now())),
+                None => self
+                    .commands
+                    .recv()
+                    .map_err(|_| RecvTimeoutError::Disconnected),
+            }
+        };
+        let first = match first {
+            Ok(command) => command,
+            Err(RecvTimeoutError::Timeout) => return Vec::new(),
+            Err(RecvTimeoutError::Disconnected) => Command::Quit,
+        };
+        iter::once(first).chain(self.commands.try_iter()).collect()
+    }
+
+    /// Carries out a command, and returns `false` if the game is to quit.
+    fn execute(&mut self, command: Command) -> Result<bool> {
+        match command {
+            Command::SlowDown => self.time_scale.slow_down(),
+            Command::SpeedUp => self.time_scale.speed_up(),
+            // The world stays paused until editing is done.
+            Command::TogglePause if self.editor.is_some() => (),
+            Command::TogglePause => self.paused = !self.paused,
+            Command::Step(generations) => {
+                self.paused = true;
+                self.steps = self.steps.saturating_add(generations);
+            }
+            Command::Rewind(frames) => {
+                self.paused = true;
+                self.rewind(frames);
+            }
+            Command::Flip => self.random_flip(),
+            Command::Reset => self.reset(),
+            Command::Save => self.save(),
+            Command::Resize => self.reflow()?,
+            Command::Pan { rows, cols } => self.pan(rows, cols),
+            Command::Center => self.center(),
+            Command::Edit(editing) => {
+                // The world stays paused until editing is done.
+                self.paused = editing;
+                self.set_editing(editing)?;
             }
+            Command::MoveCursor { rows, cols } => self.move_cursor(rows, cols),
+            Command::Toggle => self.toggle(),
+            Command::Stroke(stroke) => self.stroke(stroke),
+            Command::Help => self.toggle_help()?,
+            Command::Quit => return Ok(false),
         }
+        Ok(true)
     }
 
     fn render(&mut self) -> Result<()> {
@@ -95,23 +248,89 @@ where
             .queue(terminal::BeginSynchronizedUpdate)?
             .queue(cursor::MoveTo(0, 0))?;
 
-        let matrix = self.biosquare.observe();
+        if self.viewport.is_following()
+            && let Some(bounding_box) = self.biosquare.bounding_box()
+        {
+            self.viewport.follow(bounding_box);
+        }
 
-        for row in matrix.iter_rows() {
-            for cell in row {
-                let view = self.filter.filter(*cell);
-                self.output.queue(style::Print(view))?;
-            }
-            self.output.queue(cursor::MoveToNextLine(1))?;
+        let rect = self.viewport.rect();
+        let Rect {
+            top,
+            left,
+            nrows,
+            ncols,
+        } = rect;
+        let (height, width) = rect.extents();
+        let bottom = top + height;
+        let right = left + width;
+        let patch = self.filter.patch();
+        let (patch_height, patch_width) = Rect::new(0, 0, patch).extents();
+        let glyphs = Shape::new(nrows.div_ceil(patch.nrows), ncols.div_ceil(patch.ncols));
+
+        let biosquare = &self.biosquare;
+        let patches = (top..bottom)
+            .step_by(patch.nrows)
+            .flat_map(|row| {
+                (left..right)
+                    .step_by(patch.ncols)
+                    .map(move |col| (row, col))
+            })
+            .map(|(row, col)| {
+                // Cells of the last patches beyond the viewport are left dead.
+                let cells = (row..row + patch_height)
+                    .flat_map(|row| (col..col + patch_width).map(move |col| (row, col)))
+                    .map(|(row, col)| {
+                        if row < bottom && col < right {
+                            biosquare.get(row, col)
+                        } else {
+                            Cell::Dead
+                        }
+                    });
+                Patch::new(cells)
+            });
+
+        if self.settings.full_redraw {
+            self.canvas.invalidate();
+        }
+        self.canvas
+            .draw(&mut self.output, &self.filter, glyphs, patches)?;
+        let below = u16::try_from(glyphs.nrows).unwrap_or(u16::MAX);
+        self.output.queue(cursor::MoveTo(0, below))?;
+
+        if self.viewport.is_partial() {
+            let region = self.viewport.to_string();
+            self.output
+                .queue(terminal::Clear(terminal::ClearType::CurrentLine))?
+                .queue(style::Print(region.dim()))?
+                .queue(cursor::MoveToNextLine(1))?;
         }
 
-        if self.show_stats {
+        if self.settings.show_stats {
             self.render_stats()?;
         }
 
+        if self.showing_help {
+            self.render_help()?;
+        }
+
+        if let Some(editor) = &mut self.editor {
+            // Keeps the cursor within the viewport, wherever it has moved.
+            let rect = self.viewport.rect();
+            let (height, width) = rect.extents();
+            let (row, col) = editor.cursor;
+            let row = row.clamp(rect.top, (rect.top + height - 1).max(rect.top));
+            let col = col.clamp(rect.left, (rect.left + width - 1).max(rect.left));
+            editor.cursor = (row, col);
+
+            let (row, col) = self.glyph_at((row, col));
+            self.output.queue(cursor::MoveTo(col, row))?;
+        }
+
         self.output
             .queue(terminal::EndSynchronizedUpdate)?
             .flush()?;
+        self.frame_output = self.output.take_count();
 
         Ok(())
     }
@@ -119,18 +338,129 @@ where
     fn render_stats(&mut self) -> Result<()> {
         self.output.queue(cursor::MoveToNextLine(2))?;
 
+        let rule = self.biosquare.rule();
         let generation = self.biosquare.generation();
         let population = self.biosquare.population();
+        let range = match (self.trend.min(), self.trend.max(), self.trend.mean()) {
+            (Some(min), Some(max), Some(mean)) => format!("{min} .. {max} (mean {mean:.2})"),
+            _ => "n/a".to_string(),
+        };
+        let (changes, activity) = match self.biosquare.changes() {
+            Some(changes) => (
+                format!("{} / {}", changes.births, changes.deaths),
+                format!("{}", changes.activity()),
+            ),
+            None => ("n/a".to_string(), "n/a".to_string()),
+        };
+        let total_changes = match self.biosquare.total_changes() {
+            Some(changes) => format!("{} / {}", changes.births, changes.deaths),
+            None => "n/a".to_string(),
+        };
+        let bounding_box = self.biosquare.bounding_box().unwrap_or_default();
         let density = self.biosquare.density();
         let fps = 1.0 / self.timer.last_frame().as_secs_f64();
         let runtime = self.timer.global();
+        let frame_output = self.frame_output;
 
-        self.render_measurement("Generation", &format!("{generation}"))?
+        self.render_measurement("Rule", &format!("{rule}"))?
+            .render_measurement("Generation", &format!("{generation}"))?
             .render_measurement("Population", &format!("{population}"))?
+            .render_measurement("Population Range", &range)?
+            .render_measurement("Births / Deaths", &changes)?
+            .render_measurement("Activity", &activity)?
+            .render_measurement("Total Births / Deaths", &total_changes)?
+            .render_measurement(
+                "Bounding Box",
+                &format!("{} x {}", bounding_box.nrows, bounding_box.ncols),
+            )?
             .render_measurement("Density", &format!("{:.2} %", density * 100.0))?
             .render_measurement("FPS", &format!("{fps:.2}"))?
+            .render_measurement("Frame Output", &format!("{frame_output} B"))?
             .render_measurement("Runtime", &fmt_duration(runtime))?;
 
+        if self.detects_cycles() {
+            let stability = match self.detector.cycle() {
+                Some(cycle) => cycle.to_string(),
+                None => "evolving".to_string(),
+            };
+            self.render_measurement("Stability", &stability)?;
+        }
+
+        if self.settings.history > 0 {
+            let rewound = self.history.rewound();
+            let len = self.history.len();
+            self.render_measurement("History", &format!("{rewound} back of {len}"))?;
+        }
+
+        if let Some(editor) = &self.editor {
+            let (row, col) = editor.cursor;
+            self.render_measurement("Cursor", &format!("{row}, {col}"))?;
+        }
+
+        match &self.last_saved {
+            Some(Ok(path)) => {
+                let path = path.display().to_string();
+                self.render_measurement("Saved", &path)?;
+            }
+            Some(Err(reason)) => {
+                let reason = reason.clone();
+                self.render_measurement("Save Failed", &reason)?;
+            }
+            None => (),
+        }
+
+        let sparkline = self.trend.sparkline();
+        self.output
+            .queue(style::Print(sparkline.dim()))?
+            .queue(cursor::MoveToNextLine(1))?;
+
+        if let Some(key) = self.settings.keymap.keys(Action::Help).first() {
+            let hint = format!("Press {key} for key bindings");
+            self.output
+                .queue(style::Print(hint.dim()))?
+                .queue(cursor::MoveToNextLine(1))?;
+        }
+
+        Ok(())
+    }
+
+    /// Draws the key bindings in the top-left corner, over whatever is
+    /// there.
+    fn render_help(&mut self) -> Result<()> {
+        let bindings: Vec<_> = self
+            .settings
+            .keymap
+            .bindings()
+            .filter(|(_, keys)| !keys.is_empty())
+            .map(|(action, keys)| {
+                let keys: Vec<_> = keys.iter().map(ToString::to_string).collect();
+                let action = if action.is_editing() {
+                    format!("{action} (edit mode)")
+                } else {
+                    action.to_string()
+                };
+                (keys.join(", "), action)
+            })
+            .collect();
+        let keys_width = bindings.iter().map(|(keys, _)| keys.len()).max();
+        let keys_width = keys_width.unwrap_or(0);
+        let actions_width = bindings.iter().map(|(_, action)| action.len()).max();
+        // Each line starts and ends with a space, and spans an even number
+        // of columns to leave the glyphs two columns wide whole beside it.
+        let width = (keys_width + actions_width.unwrap_or(0) + 4).next_multiple_of(2);
+        let actions_width = width - keys_width - 4;
+
+        let title = format!(" {:<w$} ", "Key Bindings", w = width - 2);
+        self.output
+            .queue(cursor::MoveTo(0, 0))?
+            .queue(style::Print(title.bold().reverse()))?;
+        for (row, (keys, action)) in (1..).zip(bindings) {
+            let line = format!(" {keys:<keys_width$}  {action:<actions_width$} ");
+            self.output
+                .queue(cursor::MoveTo(0, row))?
+                .queue(style::Print(line.reverse()))?;
+        }
+
         Ok(())
     }
 
@@ -143,9 +473,6 @@ where
     /// Additionally, `key` and `value` should avoid containing full-width or
     /// non-printable characters, or the alignment will be incorrect.
     fn render_measurement(&mut self, key: &str, value: &str) -> Result<&mut Self> {
-        const KEY_WIDTH: usize = 20;
-        const VALUE_WIDTH: usize = 40;
-
         let key = format!("{key:<KEY_WIDTH$}");
         let value = format!("{value:>VALUE_WIDTH$}");
 
@@ -157,22 +484,247 @@ where
         Ok(self)
     }
 
-    fn wait_if_paused(&mut self) {
-        let _paused = self.timer.pause();
-        signal::PAUSE.wait_if_paused();
+    /// Shows or hides the key bindings, clearing what they covered once
+    /// hidden.
+    fn toggle_help(&mut self) -> Result<()> {
+        self.showing_help = !self.showing_help;
+        if !self.showing_help {
+            self.clear()?;
+        }
+        Ok(())
+    }
+
+    /// Moves the viewport by the given numbers of steps, each an eighth of
+    /// its size.
+    fn pan(&mut self, rows: isize, cols: isize) {
+        let (height, width) = self.viewport.rect().extents();
+        let row_step = (height / 8).max(1);
+        let col_step = (width / 8).max(1);
+        self.viewport
+            .pan(rows.saturating_mul(row_step), cols.saturating_mul(col_step));
+    }
+
+    fn center(&mut self) {
+        if let Some(centroid) = self.biosquare.centroid() {
+            self.viewport.center_at(centroid);
+        }
+    }
+
+    /// Enters or leaves edit mode, with the cursor at the center of the
+    /// viewport and the mouse captured.
+    fn set_editing(&mut self, editing: bool) -> Result<()> {
+        if editing {
+            let cursor = self.viewport.rect().center();
+            let paint = Cell::Alive;
+            self.editor = Some(Editor { cursor, paint });
+            self.output
+                .queue(event::EnableMouseCapture)?
+                .queue(cursor::Show)?;
+        } else {
+            self.editor = None;
+            self.output
+                .queue(event::DisableMouseCapture)?
+                .queue(cursor::Hide)?;
+        }
+        self.output.flush()?;
+        Ok(())
+    }
+
+    /// Moves the edit cursor by the given numbers of cells, panning the
+    /// viewport to follow it.
+    fn move_cursor(&mut self, rows: isize, cols: isize) {
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        let row = editor.cursor.0.saturating_add(rows);
+        let col = editor.cursor.1.saturating_add(cols);
+
+        let rect = self.viewport.rect();
+        if rect.nrows == 0 || rect.ncols == 0 {
+            return;
+        }
+        let Rect { top, left, .. } = rect;
+        let (height, width) = rect.extents();
+        let bottom = top + height - 1;
+        let right = left + width - 1;
+        let beyond = |n: isize, first, last| n - n.clamp(first, last);
+        self.viewport
+            .pan(beyond(row, top, bottom), beyond(col, left, right));
+        editor.cursor = (row, col);
+    }
+
+    /// Flips the cell under the edit cursor.
+    fn toggle(&mut self) {
+        if let Some(editor) = &self.editor {
+            let (row, col) = editor.cursor;
+            self.biosquare.toggle(row, col);
+            self.record_edit();
+        }
+    }
+
+    /// Flips the cell under the mouse at the start of a stroke, and paints
+    /// the cells along the rest of it the same.
+    fn stroke(&mut self, stroke: Stroke) {
+        let Some(location) = self.cell_at(stroke.row, stroke.col) else {
+            return;
+        };
+        let Some(editor) = &mut self.editor else {
+            return;
+        };
+        let (row, col) = location;
+        if stroke.start {
+            editor.paint = self.biosquare.toggle(row, col);
+        } else {
+            self.biosquare.set(row, col, editor.paint);
+        }
+        editor.cursor = location;
+        self.record_edit();
+    }
+
+    /// Returns the location of the top-left cell of the glyph at the given
+    /// terminal position, or [`None`] if it lies beyond the viewport.
+    fn cell_at(&self, row: u16, col: u16) -> Option<(isize, isize)> {
+        let rect = self.viewport.rect();
+        let patch = self.filter.patch();
+        let row = usize::from(row) * patch.nrows;
+        let col = usize::from(col / self.filter.width().max(1)) * patch.ncols;
+        (row < rect.nrows && col < rect.ncols).then(|| rect.location(Index::new(row, col)))
+    }
+
+    /// Returns the terminal position of the glyph of the given cell within
+    /// the viewport.
+    fn glyph_at(&self, (row, col): (isize, isize)) -> (u16, u16) {
+        let rect = self.viewport.rect();
+        let patch = self.filter.patch();
+        let row = row.abs_diff(rect.top) / patch.nrows;
+        let col = col.abs_diff(rect.left) / patch.ncols;
+        let row = u16::try_from(row).unwrap_or(u16::MAX);
+        let col = u16::try_from(col)
+            .unwrap_or(u16::MAX)
+            .saturating_mul(self.filter.width());
+        (row, col)
+    }
+
+    /// Evolves the world by the given number of generations, recording the
+    /// change in history and acting on it if the world stabilizes.
+    ///
+    /// Returns `false` if the game is to quit.
+    fn advance(&mut self, generations: usize) -> bool {
+        self.biosquare.advance(generations);
+        self.history.record(&mut self.biosquare);
+        self.trend.push(self.biosquare.population());
+
+        if !self.detects_cycles() || self.detector.observe(&self.biosquare).is_none() {
+            return true;
+        }
+        match self.settings.on_stable {
+            None => (),
+            Some(OnStable::Pause) => self.paused = true,
+            Some(OnStable::Quit) => return false,
+            Some(OnStable::Reseed) => self.reseed(),
+        }
+        true
+    }
+
+    /// Returns `true` if the world is checked for cycles, which is only
+    /// worth the cost if they are shown or acted on.
+    fn detects_cycles(&self) -> bool {
+        self.settings.show_stats || self.settings.on_stable.is_some()
+    }
+
+    /// Takes note of a change to the world made by other means than
+    /// evolving.
+    fn record_edit(&mut self) {
+        self.history.record(&mut self.biosquare);
+        self.detector.clear();
+    }
+
+    /// Moves through history by the given number of frames, backward if
+    /// negative.
+    fn rewind(&mut self, frames: isize) {
+        let changes = frames.unsigned_abs();
+        if frames < 0 {
+            self.history.back(&mut self.biosquare, changes);
+        } else {
+            self.history.forward(&mut self.biosquare, changes);
+        }
+        self.detector.clear();
     }
 
     fn random_flip(&mut self) {
         self.biosquare.random_flip(&mut self.rng);
+        self.record_edit();
     }
 
     fn reset(&mut self) {
-        self.biosquare = BioSquare::new(self.genesis.clone());
+        self.biosquare = self.genesis.clone();
+        self.restart();
+    }
+
+    /// Starts over from the genesis with random cells in place of its own.
+    fn reseed(&mut self) {
+        self.biosquare = self.genesis.clone();
+        self.biosquare.reseed(&mut self.rng, self.settings.density);
+        self.restart();
+    }
+
+    /// Forgets the past of the world, as if it had just begun.
+    fn restart(&mut self) {
+        self.history = History::new(self.settings.history, &mut self.biosquare);
+        self.detector.clear();
+        self.trend.clear();
+        self.trend.push(self.biosquare.population());
         self.timer = Timer::start();
     }
 
-    fn frame_duration_min(&self) -> f64 {
-        signal::TIME_SCALE.scale() / self.fps_max.0
+    /// Saves the current world to a timestamped RLE file in the working
+    /// directory.
+    ///
+    /// A failure is shown along with the statistics rather than ending the
+    /// game, which would lose the world.
+    fn save(&mut self) {
+        let timestamp = fmt_timestamp(SystemTime::now());
+        let generation = self.biosquare.generation();
+        let path = PathBuf::from(format!("lifegame-{timestamp}-{generation}.rle"));
+        let saved = match self.biosquare.save(&path) {
+            Ok(()) => Ok(path),
+            Err(error) => Err(error.root_cause().to_string()),
+        };
+        self.last_saved = Some(saved);
+    }
+
+    /// Clips the viewport to the current size of the terminal and clears it,
+    /// so that nothing is drawn past its edges.
+    fn reflow(&mut self) -> Result<()> {
+        let available = fit(&self.filter, self.settings.show_stats)?;
+        let shape = self.biosquare.shape();
+        let ncols = shape.ncols.min(available.ncols);
+        self.viewport
+            .resize(Shape::new(shape.nrows.min(available.nrows), ncols));
+        if self.viewport.is_partial() {
+            // Leaves a terminal row for the indicator of the visible region.
+            let reserved = self.filter.patch().nrows;
+            let nrows = shape.nrows.min(available.nrows.saturating_sub(reserved));
+            self.viewport.resize(Shape::new(nrows, ncols));
+        }
+
+        self.clear()
+    }
+
+    /// Clears the terminal, so that the next frame is drawn in full.
+    fn clear(&mut self) -> Result<()> {
+        self.output
+            .queue(terminal::Clear(terminal::ClearType::All))?
+            .flush()?;
+        self.canvas.invalidate();
+
+        Ok(())
+    }
+
+    /// Returns the minimum duration of a frame, or [`None`] if frames are
+    /// never due, as at a maximum fps of zero.
+    fn frame_duration(&self) -> Option<Duration> {
+        Duration::try_from_secs_f64(self.time_scale.scale() / self.settings.fps_max.0).ok()
     }
 
     fn enter_alternate_screen(&mut self) -> Result<()> {
@@ -191,6 +743,7 @@ where
         terminal::disable_raw_mode()?;
 
         self.output
+            .queue(event::DisableMouseCapture)?
             .queue(cursor::Show)?
             .queue(terminal::EnableLineWrap)?
             .queue(terminal::LeaveAlternateScreen)?
@@ -210,9 +763,53 @@ where
     }
 }
 
+/// Options of an interactive session.
+#[derive(Debug)]
+pub struct Settings {
+    pub fps_max: FpsMax,
+    /// Number of generations to evolve per frame.
+    pub step: usize,
+    pub show_stats: bool,
+    /// Number of frames kept to rewind through, or zero to keep none.
+    pub history: usize,
+    /// Whether the viewport follows the bounding box of the world.
+    pub follow: bool,
+    /// Whether to redraw every cell on every frame, rather than only the
+    /// changed ones.
+    pub full_redraw: bool,
+    pub save_on_exit: Option<PathBuf>,
+    /// Action to take once the world stabilizes, if any.
+    pub on_stable: Option<OnStable>,
+    /// Density of the random worlds to start over from.
+    pub density: Density,
+    /// Bindings of the keys, as shown on request.
+    pub keymap: Keymap,
+}
+
 #[derive(Debug, Clone, Copy)]
 pub struct FpsMax(f64);
 
+/// Returns the largest shape of the world that fits in the terminal when
+/// observed through `filter`, leaving room for the statistics if shown.
+///
+/// Each glyph of `filter` covers a patch of cells, so the shape may be larger
+/// than the terminal itself.
+pub fn fit<F>(filter: &F, show_stats: bool) -> Result<Shape>
+where
+    F: Filter,
+{
+    let (width, height) = terminal::size()?;
+    let height = if show_stats {
+        height.saturating_sub(STATS_HEIGHT)
+    } else {
+        height
+    };
+    let patch = filter.patch();
+    let nrows = usize::from(height) * patch.nrows;
+    let ncols = usize::from(width / filter.width().max(1)) * patch.ncols;
+    Ok(Shape::new(nrows, ncols))
+}
+
 impl FpsMax {
     pub fn new(value: f64) -> Option<Self> {
         if value >= 0.0 {
@@ -223,6 +820,48 @@ impl FpsMax {
     }
 }
 
+/// State of edit mode.
+#[derive(Debug, Clone, Copy)]
+struct Editor {
+    /// Location of the cursor.
+    cursor: (isize, isize),
+    /// Cell painted along a mouse stroke.
+    paint: Cell,
+}
+
+/// Writer that counts the bytes written through it.
+#[derive(Debug)]
+struct Counted<W> {
+    inner: W,
+    count: usize,
+}
+
+impl<W> Counted<W> {
+    fn new(inner: W) -> Self {
+        Self { inner, count: 0 }
+    }
+
+    /// Returns the number of bytes written since the last call.
+    fn take_count(&mut self) -> usize {
+        std::mem::take(&mut self.count)
+    }
+}
+
+impl<W> Write for Counted<W>
+where
+    W: Write,
+{
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.count += written;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
 #[derive(Debug)]
 struct Timer {
     global_start: Instant,
@@ -264,6 +903,72 @@ impl Timer {
     }
 }
 
+/// Factor of the duration of frames, as a power of two.
+#[derive(Debug, Default)]
+struct TimeScale {
+    exponent: i8,
+}
+
+impl TimeScale {
+    const MAX_EXPONENT: i8 = 10;
+    const MIN_EXPONENT: i8 = -10;
+
+    fn slow_down(&mut self) {
+        self.exponent = (self.exponent + 1).min(Self::MAX_EXPONENT);
+    }
+
+    fn speed_up(&mut self) {
+        self.exponent = (self.exponent - 1).max(Self::MIN_EXPONENT);
+    }
+
+    fn scale(&self) -> f64 {
+        f64::from(self.exponent).exp2()
+    }
+}
+
+/// Deadlines of the frames, evenly spaced by their minimum duration.
+#[derive(Debug)]
+struct Scheduler {
+    /// When the last frame was due.
+    last: Instant,
+}
+
+impl Scheduler {
+    fn start() -> Self {
+        let last = Instant::now();
+        Self { last }
+    }
+
+    /// Returns when the next frame is due, or [`None`] if never.
+    ///
+    /// The duration of a frame is taken anew every time, so that a change of
+    /// speed applies to the frame already under way.
+    fn deadline(&self, frame: Option<Duration>) -> Option<Instant> {
+        self.last.checked_add(frame?)
+    }
+
+    fn is_due(&self, frame: Option<Duration>) -> bool {
+        self.deadline(frame)
+            .is_some_and(|deadline| deadline <= Instant::now())
+    }
+
+    /// Moves on to the frame after the one due.
+    ///
+    /// Frames stay due at multiples of their duration, so that the time
+    /// spent on each does not add up to drift. If more than a frame behind,
+    /// as after a pause or a slow generation, they start over from now
+    /// rather than rush to catch up.
+    fn next(&mut self, frame: Option<Duration>) {
+        let now = Instant::now();
+        self.last = match (self.deadline(frame), frame) {
+            (Some(deadline), Some(frame)) if now.saturating_duration_since(deadline) <= frame => {
+                deadline
+            }
+            _ => now,
+        };
+    }
+}
+
 #[derive(Debug)]
 struct PausedTimer<'a> {
     start: Instant,
@@ -294,3 +999,35 @@ fn fmt_duration(duration: Duration) -> String {
 
     format!("{secs} s {millis:>03} ms {micros:>03} μs {nanos:>03} ns")
 }
+
+/// Formats `time` as a compact ISO 8601 UTC timestamp, e.g. `20240229T235959Z`.
+fn fmt_timestamp(time: SystemTime) -> String {
+    const SECS_PER_DAY: u64 = 24 * 60 * 60;
+
+    let secs = time
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let days = secs / SECS_PER_DAY;
+    let secs = secs % SECS_PER_DAY;
+
+    // Converts days since 1970-01-01 to a civil date in the proleptic
+    // Gregorian calendar, with eras of 400 years (146097 days) starting on
+    // March 1st, 0000.
+    let days = days + 719468;
+    let era = days / 146097;
+    let day_of_era = days % 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * month + 2) / 5 + 1;
+    let month = if month < 10 { month + 3 } else { month - 9 };
+    let year = era * 400 + year_of_era + u64::from(month <= 2);
+
+    let hour = secs / 3600;
+    let minute = secs / 60 % 60;
+    let second = secs % 60;
+
+    format!("{year:04}{month:02}{day:02}T{hour:02}{minute:02}{second:02}Z")
+}
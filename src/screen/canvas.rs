@@ -0,0 +1,121 @@
+use crate::filter::{Filter, Patch};
+use crossterm::QueueableCommand;
+use crossterm::cursor;
+use crossterm::style::{self, Attribute, Color, ContentStyle};
+use matreex::Shape;
+use std::io::{self, Write};
+
+/// Grid of glyphs on the terminal that only redraws the patches that changed
+/// since the last frame.
+#[derive(Debug, Default)]
+pub struct Canvas {
+    /// Shape of the grid, in glyphs.
+    shape: Shape,
+    /// Patches on the terminal, or [`None`] where unknown.
+    patches: Vec<Option<Patch>>,
+}
+
+impl Canvas {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets what is on the terminal, so that the next frame is drawn in
+    /// full.
+    pub fn invalidate(&mut self) {
+        self.patches.fill(None);
+    }
+
+    /// Draws a frame of the given shape, in glyphs, from its `patches` in
+    /// row-major order, starting at the top-left corner of the terminal.
+    ///
+    /// Runs of changed glyphs are written without moving the cursor in
+    /// between, and styles are only changed where they differ.
+    pub fn draw<F, W, I>(
+        &mut self,
+        output: &mut W,
+        filter: &F,
+        shape: Shape,
+        patches: I,
+    ) -> io::Result<()>
+    where
+        F: Filter,
+        W: Write,
+        I: IntoIterator<Item = Patch>,
+    {
+        if shape != self.shape {
+            self.shape = shape;
+            self.patches = vec![None; shape.nrows * shape.ncols];
+        }
+
+        let width = filter.width();
+        let mut next = None;
+        let mut current_style = None;
+
+        for (index, patch) in patches.into_iter().enumerate().take(self.patches.len()) {
+            if self.patches[index] == Some(patch) {
+                continue;
+            }
+            self.patches[index] = Some(patch);
+
+            let location = (index / shape.ncols, index % shape.ncols);
+            if next != Some(location) {
+                // Locations beyond the terminal are clipped by it anyway.
+                let row = u16::try_from(location.0).unwrap_or(u16::MAX);
+                let col = u16::try_from(location.1)
+                    .unwrap_or(u16::MAX)
+                    .saturating_mul(width);
+                output.queue(cursor::MoveTo(col, row))?;
+            }
+
+            let glyph = filter.filter(patch);
+            restyle(output, current_style, *glyph.style())?;
+            current_style = Some(*glyph.style());
+            output.queue(style::Print(glyph.content()))?;
+            next = Some((location.0, location.1 + 1));
+        }
+
+        if current_style.is_some() {
+            output.queue(style::SetAttribute(Attribute::Reset))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Switches the terminal from the `current` style, or an unknown one if
+/// [`None`], to the `target` style.
+fn restyle<W>(output: &mut W, current: Option<ContentStyle>, target: ContentStyle) -> io::Result<()>
+where
+    W: Write,
+{
+    if current == Some(target) {
+        return Ok(());
+    }
+
+    // Attributes cannot be turned off individually, but resetting them also
+    // resets the colors.
+    let current = match current {
+        Some(current) if current.attributes == target.attributes => current,
+        _ => {
+            output
+                .queue(style::SetAttribute(Attribute::Reset))?
+                .queue(style::SetAttributes(target.attributes))?;
+            ContentStyle {
+                attributes: target.attributes,
+                ..ContentStyle::default()
+            }
+        }
+    };
+
+    if current.foreground_color != target.foreground_color {
+        let color = target.foreground_color.unwrap_or(Color::Reset);
+        output.queue(style::SetForegroundColor(color))?;
+    }
+    if current.background_color != target.background_color {
+        let color = target.background_color.unwrap_or(Color::Reset);
+        output.queue(style::SetBackgroundColor(color))?;
+    }
+
+    Ok(())
+}
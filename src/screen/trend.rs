@@ -0,0 +1,70 @@
+use std::collections::VecDeque;
+
+/// Rolling record of the population over the last frames.
+#[derive(Debug)]
+pub struct Trend {
+    capacity: usize,
+    /// Populations, oldest first.
+    samples: VecDeque<usize>,
+}
+
+impl Trend {
+    /// Glyphs of the sparkline, from the lowest to the highest.
+    const LEVELS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    /// Creates an empty trend of up to `capacity` samples.
+    pub fn new(capacity: usize) -> Self {
+        let samples = VecDeque::with_capacity(capacity);
+        Self { capacity, samples }
+    }
+
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+
+    /// Records a population, forgetting the oldest one if full.
+    pub fn push(&mut self, population: usize) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(population);
+    }
+
+    pub fn min(&self) -> Option<usize> {
+        self.samples.iter().copied().min()
+    }
+
+    pub fn max(&self) -> Option<usize> {
+        self.samples.iter().copied().max()
+    }
+
+    pub fn mean(&self) -> Option<f64> {
+        if self.samples.is_empty() {
+            return None;
+        }
+        let sum: f64 = self.samples.iter().map(|&sample| sample as f64).sum();
+        Some(sum / self.samples.len() as f64)
+    }
+
+    /// Returns one glyph per sample, scaled between the lowest and the
+    /// highest, and padded on the left to the full capacity.
+    pub fn sparkline(&self) -> String {
+        let (Some(min), Some(max)) = (self.min(), self.max()) else {
+            return " ".repeat(self.capacity);
+        };
+        let range = (max - min) as f64;
+        let top = Self::LEVELS.len() - 1;
+
+        let padding = self.capacity - self.samples.len();
+        let glyphs = self.samples.iter().map(|&sample| {
+            // A flat trend sits at the bottom.
+            let level = if range == 0.0 {
+                0
+            } else {
+                ((sample - min) as f64 / range * top as f64).round() as usize
+            };
+            Self::LEVELS[level]
+        });
+        std::iter::repeat_n(' ', padding).chain(glyphs).collect()
+    }
+}
@@ -0,0 +1,135 @@
+use crate::biosquare::BioSquare;
+use clap::ValueEnum;
+use std::collections::{HashMap, VecDeque};
+use std::fmt::{self, Display, Formatter};
+
+/// Action to take once the world stabilizes.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum OnStable {
+    /// Pause the world.
+    Pause,
+
+    /// Exit the game.
+    Quit,
+
+    /// Start over from a random world.
+    Reseed,
+}
+
+/// Repetition of the states of the world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cycle {
+    /// Earliest generation observed within the cycle.
+    pub generation: usize,
+    /// Number of generations between repetitions.
+    pub period: usize,
+}
+
+impl Display for Cycle {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "stabilized at generation {} with period {}",
+            self.generation, self.period
+        )
+    }
+}
+
+/// Detects when the world starts repeating itself, by remembering digests
+/// of the recent states it is shown.
+///
+/// A world observed every few generations is only known to repeat once a
+/// multiple of its period has passed, so the period is then narrowed down by
+/// evolving a copy of it.
+#[derive(Debug, Default)]
+pub struct Detector {
+    /// Generation at which each recent state was observed, by digest.
+    seen: HashMap<u64, usize>,
+    /// Digests of the recent states, oldest first.
+    order: VecDeque<u64>,
+    /// Generation of the last state observed.
+    last: Option<usize>,
+    /// Whether some generations were not observed, in which case a repetition
+    /// may span several periods.
+    skipped: bool,
+    cycle: Option<Cycle>,
+}
+
+impl Detector {
+    /// Maximum number of states remembered, which bounds the periods that
+    /// can be detected.
+    const STATES_MAX: usize = 1 << 12;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the cycle the world has settled into, if detected.
+    pub fn cycle(&self) -> Option<Cycle> {
+        self.cycle
+    }
+
+    /// Forgets the states observed so far, as after the world is changed by
+    /// other means than evolving.
+    pub fn clear(&mut self) {
+        *self = Self::new();
+    }
+
+    /// Observes the current state of `biosquare`, and returns the cycle it
+    /// has settled into if this is when it is first detected.
+    pub fn observe(&mut self, biosquare: &BioSquare) -> Option<Cycle> {
+        if self.cycle.is_some() {
+            return None;
+        }
+        let digest = biosquare.digest();
+        let generation = biosquare.generation();
+        self.skipped |= self.last.is_some_and(|last| generation != last + 1);
+        self.last = Some(generation);
+
+        let Some(&first) = self.seen.get(&digest) else {
+            self.seen.insert(digest, generation);
+            self.order.push_back(digest);
+            if self.order.len() > Self::STATES_MAX
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.seen.remove(&oldest);
+            }
+            return None;
+        };
+        let distance = generation
+            .checked_sub(first)
+            .filter(|&distance| distance > 0)?;
+
+        // Had the period been shorter, the state would have repeated at an
+        // earlier generation, unless that generation was skipped.
+        let period = if self.skipped {
+            period(biosquare, digest, distance)
+        } else {
+            distance
+        };
+        let cycle = Cycle {
+            generation: first,
+            period,
+        };
+        self.cycle = Some(cycle);
+        Some(cycle)
+    }
+}
+
+/// Returns the smallest period of `biosquare`, whose state has the given
+/// digest and repeats after `distance` generations.
+///
+/// The period is the smallest divisor of `distance` after which the state
+/// repeats, so only those are tried, in increasing order.
+fn period(biosquare: &BioSquare, digest: u64, distance: usize) -> usize {
+    let mut copy = biosquare.clone();
+    let mut evolved = 0;
+    for divisor in (1..=distance).filter(|&divisor| distance.is_multiple_of(divisor)) {
+        copy.advance(divisor - evolved);
+        evolved = divisor;
+        if copy.digest() == digest {
+            return divisor;
+        }
+    }
+    distance
+}
@@ -0,0 +1,192 @@
+use crate::biosquare::Rect;
+use clap::ValueEnum;
+use matreex::{Index, Shape};
+use std::fmt::{self, Display, Formatter};
+
+/// Boundary topology of the world.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+pub enum Topology {
+    /// Both axes wrap around.
+    #[default]
+    Torus,
+
+    /// Cells beyond the edges are always dead.
+    Plane,
+
+    /// Edges reflect, as if the world were mirrored across them.
+    Mirror,
+
+    /// Columns wrap around; rows wrap around with columns reversed.
+    Klein,
+
+    /// Columns wrap around; cells beyond the top and bottom edges are always
+    /// dead.
+    Cylinder,
+
+    /// The world extends infinitely beyond what is observed.
+    Infinite,
+}
+
+impl Topology {
+    /// Returns the edges of the world, or [`None`] if it is infinite.
+    pub fn edges(self) -> Option<Edges> {
+        let (rows, cols) = match self {
+            Self::Torus => (Edge::Wrap, Edge::Wrap),
+            Self::Plane => (Edge::Dead, Edge::Dead),
+            Self::Mirror => (Edge::Reflect, Edge::Reflect),
+            Self::Klein => (Edge::Twist, Edge::Wrap),
+            Self::Cylinder => (Edge::Dead, Edge::Wrap),
+            Self::Infinite => return None,
+        };
+        Some(Edges { rows, cols })
+    }
+}
+
+impl Display for Topology {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let value = self.to_possible_value().unwrap_or_else(|| unreachable!());
+        f.write_str(value.get_name())
+    }
+}
+
+/// Edges along the row axis and the column axis of a bounded world.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Edges {
+    pub rows: Edge,
+    pub cols: Edge,
+}
+
+impl Edges {
+    /// Resolves a possibly out-of-bounds location to a cell within `shape`.
+    ///
+    /// Returns [`None`] if the location lies beyond a dead border.
+    ///
+    /// # Notes
+    ///
+    /// `shape` must not be empty, and the location must not be further than
+    /// one world length away from it.
+    pub fn resolve(self, row: isize, col: isize, shape: Shape) -> Option<Index> {
+        let (nrows, ncols) = Rect::new(0, 0, shape).extents();
+
+        let (row, twisted) = self.rows.resolve(row, nrows)?;
+        let (col, _) = self.cols.resolve(col, ncols)?;
+        let col = if twisted { ncols - 1 - col } else { col };
+
+        // Resolved locations lie within the world, so the following casts
+        // are always safe.
+        Some(Index::new(row as usize, col as usize))
+    }
+}
+
+/// Behavior of the world at both ends of an axis.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Edge {
+    /// Cells beyond the ends are always dead.
+    Dead,
+
+    /// The axis wraps around.
+    Wrap,
+
+    /// The axis wraps around, and crossing the ends reverses the other axis.
+    Twist,
+
+    /// The ends reflect, as if the world were mirrored across them.
+    Reflect,
+}
+
+impl Edge {
+    /// Resolves a possibly out-of-bounds position along an axis of length
+    /// `len`, along with whether the other axis is reversed.
+    ///
+    /// Returns [`None`] if the position lies beyond a dead end.
+    ///
+    /// # Notes
+    ///
+    /// `len` must not be zero, and the position must not be further than
+    /// `len` away from the axis.
+    pub fn resolve(self, n: isize, len: isize) -> Option<(isize, bool)> {
+        match self {
+            Self::Dead => (0..len).contains(&n).then_some((n, false)),
+            Self::Wrap => Some((n.rem_euclid(len), false)),
+            Self::Twist => Some((n.rem_euclid(len), n.div_euclid(len) % 2 != 0)),
+            Self::Reflect => {
+                let n = if n < 0 {
+                    -1 - n
+                } else if n >= len {
+                    len - 1 - (n - len)
+                } else {
+                    n
+                };
+                Some((n, false))
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SHAPE: Shape = Shape { nrows: 3, ncols: 4 };
+
+    fn resolve(topology: Topology, row: isize, col: isize) -> Option<(usize, usize)> {
+        let edges = topology.edges().unwrap();
+        let index = edges.resolve(row, col, SHAPE)?;
+        Some((index.row, index.col))
+    }
+
+    #[test]
+    fn keeps_locations_within_bounds() {
+        let topologies = Topology::value_variants()
+            .iter()
+            .filter(|topology| topology.edges().is_some());
+        for &topology in topologies {
+            assert_eq!(resolve(topology, 0, 0), Some((0, 0)), "{topology}");
+            assert_eq!(resolve(topology, 2, 3), Some((2, 3)), "{topology}");
+            assert_eq!(resolve(topology, 1, 2), Some((1, 2)), "{topology}");
+        }
+    }
+
+    #[test]
+    fn wraps_torus() {
+        assert_eq!(resolve(Topology::Torus, -1, -1), Some((2, 3)));
+        assert_eq!(resolve(Topology::Torus, 3, 4), Some((0, 0)));
+        assert_eq!(resolve(Topology::Torus, -1, 4), Some((2, 0)));
+    }
+
+    #[test]
+    fn kills_beyond_dead_edges() {
+        assert_eq!(resolve(Topology::Plane, -1, 0), None);
+        assert_eq!(resolve(Topology::Plane, 0, 4), None);
+        assert_eq!(resolve(Topology::Plane, 3, -1), None);
+        assert_eq!(resolve(Topology::Cylinder, 0, -1), Some((0, 3)));
+        assert_eq!(resolve(Topology::Cylinder, 1, 4), Some((1, 0)));
+        assert_eq!(resolve(Topology::Cylinder, -1, 0), None);
+        assert_eq!(resolve(Topology::Cylinder, 3, 2), None);
+    }
+
+    #[test]
+    fn reflects_mirror() {
+        assert_eq!(resolve(Topology::Mirror, -1, 2), Some((0, 2)));
+        assert_eq!(resolve(Topology::Mirror, 3, 2), Some((2, 2)));
+        assert_eq!(resolve(Topology::Mirror, 1, -1), Some((1, 0)));
+        assert_eq!(resolve(Topology::Mirror, 1, 4), Some((1, 3)));
+        assert_eq!(resolve(Topology::Mirror, -1, -1), Some((0, 0)));
+        assert_eq!(resolve(Topology::Mirror, 3, 4), Some((2, 3)));
+    }
+
+    #[test]
+    fn twists_klein_bottle() {
+        // Crossing the left or right edge only wraps the columns.
+        assert_eq!(resolve(Topology::Klein, 1, -1), Some((1, 3)));
+        assert_eq!(resolve(Topology::Klein, 1, 4), Some((1, 0)));
+        // Crossing the top or bottom edge also reverses the columns.
+        assert_eq!(resolve(Topology::Klein, -1, 1), Some((2, 2)));
+        assert_eq!(resolve(Topology::Klein, 3, 0), Some((0, 3)));
+        // Crossing a corner does both.
+        assert_eq!(resolve(Topology::Klein, -1, -1), Some((2, 0)));
+        assert_eq!(resolve(Topology::Klein, -1, 4), Some((2, 3)));
+        assert_eq!(resolve(Topology::Klein, 3, -1), Some((0, 0)));
+        assert_eq!(resolve(Topology::Klein, 3, 4), Some((0, 3)));
+    }
+}
@@ -0,0 +1,105 @@
+use crate::biosquare::Rect;
+use matreex::Shape;
+use std::fmt::{self, Display, Formatter};
+
+/// Region of the world shown on the screen.
+#[derive(Debug, Clone, Copy)]
+pub struct Viewport {
+    rect: Rect,
+    /// Shape of the world, or [`None`] if it is infinite.
+    world: Option<Shape>,
+    following: bool,
+}
+
+impl Viewport {
+    /// Creates an empty viewport at the top-left corner of the genesis.
+    ///
+    /// If `follow` is `true`, the viewport keeps centered on the bounding box
+    /// of the world until moved by hand.
+    pub fn new(world: Option<Shape>, follow: bool) -> Self {
+        Self {
+            rect: Rect::default(),
+            world,
+            following: follow,
+        }
+    }
+
+    pub fn rect(&self) -> Rect {
+        self.rect
+    }
+
+    /// Returns `true` if part of the world lies outside the viewport.
+    pub fn is_partial(&self) -> bool {
+        match self.world {
+            Some(world) => self.rect.nrows < world.nrows || self.rect.ncols < world.ncols,
+            None => true,
+        }
+    }
+
+    /// Changes the shape of the viewport, keeping its top-left corner.
+    pub fn resize(&mut self, shape: Shape) {
+        self.rect.nrows = shape.nrows;
+        self.rect.ncols = shape.ncols;
+        self.clamp();
+    }
+
+    /// Moves the viewport by the given numbers of rows and columns, and stops
+    /// following the world.
+    pub fn pan(&mut self, rows: isize, cols: isize) {
+        self.rect.top = self.rect.top.saturating_add(rows);
+        self.rect.left = self.rect.left.saturating_add(cols);
+        self.following = false;
+        self.clamp();
+    }
+
+    /// Centers the viewport at the given location, and stops following the
+    /// world.
+    pub fn center_at(&mut self, location: (isize, isize)) {
+        self.rect = self.rect.centered_at(location);
+        self.following = false;
+        self.clamp();
+    }
+
+    pub fn is_following(&self) -> bool {
+        self.following
+    }
+
+    /// Centers the viewport on the bounding box of the world, and keeps
+    /// following it.
+    pub fn follow(&mut self, bounding_box: Rect) {
+        self.rect = self.rect.centered_at(bounding_box.center());
+        self.clamp();
+    }
+
+    /// Keeps the viewport within a bounded world.
+    fn clamp(&mut self) {
+        let Some(world) = self.world else {
+            return;
+        };
+        let (nrows, ncols) = Rect::new(0, 0, world).extents();
+        let (height, width) = self.rect.extents();
+        let bottom = (nrows - height).max(0);
+        let right = (ncols - width).max(0);
+        self.rect.top = self.rect.top.clamp(0, bottom);
+        self.rect.left = self.rect.left.clamp(0, right);
+    }
+}
+
+impl Display for Viewport {
+    /// Formats the visible rows and columns as half-open ranges, along with
+    /// the shape of the world if it is bounded.
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let Rect { top, left, .. } = self.rect;
+        let (height, width) = self.rect.extents();
+        let bottom = top + height;
+        let right = left + width;
+        match self.world {
+            Some(world) => write!(
+                f,
+                "rows {top}..{bottom} of {}, cols {left}..{right} of {}",
+                world.nrows, world.ncols
+            ),
+            None => write!(f, "rows {top}..{bottom}, cols {left}..{right} (infinite)"),
+        }
+    }
+}